@@ -0,0 +1,90 @@
+// What a part of a puzzle comes out as. Mostly numbers, but some puzzles spell
+// their answer out in ASCII art, and some parts just aren't done yet.
+
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i64),
+    /// Anything too big for an i64.
+    BigInt(i128),
+    Str(String),
+    /// Several lines of text, e.g. letters drawn out in `#`s.
+    Grid(Vec<String>),
+    Unimplemented,
+}
+impl Answer {
+    pub fn is_implemented(&self) -> bool {
+        !matches!(self, Answer::Unimplemented)
+    }
+}
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::BigInt(n) => write!(f, "{n}"),
+            Answer::Str(s) => write!(f, "{s}"),
+            Answer::Grid(lines) => write!(f, "{}", lines.join("\n")),
+            Answer::Unimplemented => write!(f, "(not implemented)"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ident),*) => {$(
+        impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                match i64::try_from(n) {
+                    Ok(n) => Answer::Int(n),
+                    Err(_) => match i128::try_from(n) {
+                        Ok(n) => Answer::BigInt(n),
+                        // Only a u128 can get here, and there's nowhere else to put it
+                        Err(_) => Answer::Str(n.to_string()),
+                    },
+                }
+            }
+        }
+    )*};
+}
+answer_from_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Str(s.to_string())
+    }
+}
+impl From<Vec<String>> for Answer {
+    fn from(lines: Vec<String>) -> Self {
+        Answer::Grid(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_numbers() {
+        assert_eq!(Answer::Int(42), 42usize.into());
+        assert_eq!(Answer::Int(-7), (-7isize).into());
+        assert_eq!(Answer::BigInt(u64::MAX as i128), u64::MAX.into());
+        assert_eq!(Answer::BigInt(i128::MIN), i128::MIN.into());
+        assert_eq!(Answer::Str(u128::MAX.to_string()), u128::MAX.into());
+    }
+
+    #[test]
+    fn should_display() {
+        assert_eq!("1234", Answer::from(1234u32).to_string());
+        assert_eq!("ABC", Answer::from("ABC").to_string());
+        let grid = Answer::from(vec!["#..#".to_string(), "####".to_string()]);
+        assert_eq!("#..#\n####", grid.to_string());
+        assert_eq!("(not implemented)", Answer::Unimplemented.to_string());
+        assert!(!Answer::Unimplemented.is_implemented());
+        assert!(grid.is_implemented());
+    }
+}
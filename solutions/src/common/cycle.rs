@@ -0,0 +1,174 @@
+// For simulations that are asked to run for far longer than they could, but
+// that eventually start repeating themselves.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The shape of a sequence that starts repeating: the first `prefix` states
+/// are never seen again, then the next `length` states repeat forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub length: usize,
+}
+impl Cycle {
+    /// The earliest step that's in the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        match n < self.prefix {
+            true => n,
+            false => self.prefix + (n - self.prefix) % self.length,
+        }
+    }
+}
+
+/// Floyd's tortoise and hare. Only needs to hold a couple of states at a
+/// time, at the cost of calling `step` roughly three times as often as the
+/// hash-based version.
+pub fn floyd<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let halfway = step(&hare);
+        hare = step(&halfway);
+    }
+
+    let mut prefix = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle { prefix, length }
+}
+
+/// Brent's algorithm. Same memory use as Floyd's but usually fewer steps.
+pub fn brent<S: Clone + Eq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, length }
+}
+
+/// Remembers every state until one comes round again. Returns the cycle along
+/// with every state seen, so that any step's state can be looked up.
+pub fn find_cycle<S: Clone + Hash + Eq>(
+    start: S,
+    mut step: impl FnMut(&S) -> S,
+) -> (Cycle, Vec<S>) {
+    let mut seen = HashMap::from([(start.clone(), 0)]);
+    let mut history = vec![start];
+    loop {
+        let next = step(history.last().unwrap());
+        if let Some(&prefix) = seen.get(&next) {
+            let length = history.len() - prefix;
+            return (Cycle { prefix, length }, history);
+        }
+        seen.insert(next.clone(), history.len());
+        history.push(next);
+    }
+}
+
+/// The state after `n` steps. Stops simulating as soon as it spots a cycle,
+/// so `n` can be as big as you like.
+pub fn nth_state<S: Clone + Hash + Eq>(start: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut seen = HashMap::from([(start.clone(), 0)]);
+    let mut history = vec![start];
+    while history.len() <= n {
+        let next = step(history.last().unwrap());
+        if let Some(&prefix) = seen.get(&next) {
+            let cycle = Cycle {
+                prefix,
+                length: history.len() - prefix,
+            };
+            return history.swap_remove(cycle.reduce(n));
+        }
+        seen.insert(next.clone(), history.len());
+        history.push(next);
+    }
+    history.swap_remove(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(n: &usize) -> usize {
+        match n {
+            6 => 3,
+            _ => n + 1,
+        }
+    }
+    const EXPECTED: Cycle = Cycle {
+        prefix: 3,
+        length: 4,
+    };
+
+    #[test]
+    fn should_find_cycles() {
+        assert_eq!(EXPECTED, floyd(0, step));
+        assert_eq!(EXPECTED, brent(0, step));
+        let (cycle, history) = find_cycle(0, step);
+        assert_eq!(EXPECTED, cycle);
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], history);
+    }
+
+    #[test]
+    fn should_find_cycles_from_the_start() {
+        let step = |n: &u8| (n + 1) % 5;
+        let expected = Cycle {
+            prefix: 0,
+            length: 5,
+        };
+        assert_eq!(expected, floyd(0, step));
+        assert_eq!(expected, brent(0, step));
+        assert_eq!(expected, find_cycle(0, step).0);
+        assert_eq!(
+            Cycle {
+                prefix: 0,
+                length: 1
+            },
+            brent(7, |&n| n)
+        );
+    }
+
+    #[test]
+    fn should_get_nth_state() {
+        assert_eq!(0, nth_state(0, step, 0));
+        assert_eq!(2, nth_state(0, step, 2));
+        assert_eq!(6, nth_state(0, step, 6));
+        assert_eq!(3, nth_state(0, step, 7));
+        // 1e12 - 3 is a multiple of 4 plus 1
+        assert_eq!(4, nth_state(0, step, 1_000_000_000_000));
+        assert_eq!(EXPECTED.reduce(1_000_000_000_000), 4);
+    }
+}
@@ -0,0 +1,105 @@
+// Differential testing: generate small random puzzle inputs and check that a
+// day's fast solution agrees with a slow but obviously correct one. Run them
+// all with `cargo test differential`.
+
+use proptest::{
+    strategy::Strategy,
+    test_runner::{Config, TestCaseError, TestError, TestRunner},
+};
+use std::{fmt::Debug, fs, path::PathBuf};
+
+/// A generated puzzle input, plus anything else the implementations are
+/// given (a step count, say).
+pub trait Case: Clone + Debug {
+    fn input(&self) -> &str;
+}
+impl Case for String {
+    fn input(&self) -> &str {
+        self
+    }
+}
+impl<T: Clone + Debug> Case for (String, T) {
+    fn input(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Where the minimized input for a failing check called `name` is written.
+pub fn dump_path(name: &str) -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target"));
+    target.join("differential").join(format!("{name}.txt"))
+}
+
+/// Runs `test` against inputs from `strategy`, panicking if it ever fails.
+/// Proptest shrinks the failure first, and the input it ends up with is
+/// written to `dump_path(name)` so it can be fed to the solver directly.
+pub fn check<S>(name: &str, strategy: S, test: impl Fn(&S::Value) -> Result<(), TestCaseError>)
+where
+    S: Strategy,
+    S::Value: Case,
+{
+    check_with(name, Config::default(), strategy, test)
+}
+
+/// Like `check`, for when the reference is slow enough to want fewer cases.
+pub fn check_with<S>(
+    name: &str,
+    config: Config,
+    strategy: S,
+    test: impl Fn(&S::Value) -> Result<(), TestCaseError>,
+) where
+    S: Strategy,
+    S::Value: Case,
+{
+    let config = Config {
+        // The dump does that job, without touching the source tree
+        failure_persistence: None,
+        ..config
+    };
+    match TestRunner::new(config).run(&strategy, |case| test(&case)) {
+        Ok(()) => (),
+        Err(TestError::Fail(reason, case)) => {
+            let path = dump_path(name);
+            let written = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, case.input()));
+            let saved = match written {
+                Ok(()) => format!("input written to {}", path.display()),
+                Err(e) => format!("couldn't write the input to {}: {e}", path.display()),
+            };
+            panic!("{name}: implementations disagree on {case:?}\n{reason}\n{saved}");
+        }
+        Err(TestError::Abort(reason)) => panic!("{name}: gave up: {reason}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn should_pass_when_they_agree() {
+        check("agree", "[a-z]{0,5}", |s| {
+            prop_assert_eq!(s.len(), s.chars().count());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn should_dump_the_smallest_failure() {
+        let path = dump_path("disagree");
+        let _ = fs::remove_file(&path);
+        let result = std::panic::catch_unwind(|| {
+            check("disagree", ("[a-z]{0,10}", 0..10usize), |(s, n)| {
+                prop_assert!(s.len() < 3 || *n < 5);
+                Ok(())
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!("aaa", fs::read_to_string(&path).unwrap());
+    }
+}
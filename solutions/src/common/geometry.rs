@@ -0,0 +1,282 @@
+// Geometry beyond the flat grid: boxes, arbitrary dimensions, and real-valued
+// vectors for when things stop landing on integer coordinates.
+
+use super::Point3;
+use impl_ops::*;
+use std::ops;
+
+/// An axis-aligned box, inclusive of both corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<T = isize> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+}
+impl<T: Copy + Ord> Cuboid<T> {
+    /// Builds the box spanning two opposite corners, in either order.
+    pub fn new(a: Point3<T>, b: Point3<T>) -> Self {
+        Cuboid {
+            min: Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// The smallest box containing every point, or None if there aren't any.
+    pub fn bounding(points: impl IntoIterator<Item = Point3<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Cuboid::new(first, first), |acc, p| {
+            Cuboid::new(
+                Point3::new(acc.min.x.min(p.x), acc.min.y.min(p.y), acc.min.z.min(p.z)),
+                Point3::new(acc.max.x.max(p.x), acc.max.y.max(p.y), acc.max.z.max(p.z)),
+            )
+        }))
+    }
+
+    pub fn contains(&self, p: &Point3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point3::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
+        );
+        let max = Point3::new(
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
+        );
+        match min.x <= max.x && min.y <= max.y && min.z <= max.z {
+            true => Some(Cuboid { min, max }),
+            false => None,
+        }
+    }
+}
+
+macro_rules! cuboid_volume {
+    ($($t:ident),*) => {$(
+        impl Cuboid<$t> {
+            /// The number of integer points inside the box.
+            pub fn volume(&self) -> u128 {
+                let len = |a: $t, b: $t| (b.abs_diff(a) as u128) + 1;
+                len(self.min.x, self.max.x) * len(self.min.y, self.max.y) * len(self.min.z, self.max.z)
+            }
+        }
+    )*};
+}
+cuboid_volume!(usize, u64, u32, isize, i64, i32);
+
+/// A point with any number of coordinates, for when two or three aren't enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PointN<const N: usize>(pub [isize; N]);
+impl<const N: usize> PointN<N> {
+    pub fn origin() -> Self {
+        PointN([0; N])
+    }
+
+    pub fn manhattan(&self, other: &Self) -> usize {
+        self.0.iter().zip(other.0).map(|(a, b)| a.abs_diff(b)).sum()
+    }
+
+    /// The 2N neighbors that differ by one along a single axis.
+    pub fn neighbors(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..N).flat_map(move |axis| {
+            [-1, 1].map(|d| {
+                let mut next = *self;
+                next.0[axis] += d;
+                next
+            })
+        })
+    }
+
+    /// All 3^N - 1 surrounding neighbors.
+    pub fn neighbors_diagonal(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..3usize.pow(N as u32)).filter_map(move |mut i| {
+            let mut next = *self;
+            for c in next.0.iter_mut() {
+                *c += (i % 3) as isize - 1;
+                i /= 3;
+            }
+            match next == *self {
+                true => None,
+                false => Some(next),
+            }
+        })
+    }
+}
+impl<const N: usize> ops::Add for PointN<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a += b);
+        self
+    }
+}
+impl<const N: usize> ops::Sub for PointN<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        self.0.iter_mut().zip(rhs.0).for_each(|(a, b)| *a -= b);
+        self
+    }
+}
+
+const EPSILON: f64 = 1e-9;
+
+/// A real-valued 3D vector.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl Vec3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+impl From<Point3<isize>> for Vec3 {
+    fn from(p: Point3<isize>) -> Self {
+        Vec3::new(p.x as f64, p.y as f64, p.z as f64)
+    }
+}
+impl_op_ex!(+ |a: &Vec3, b: &Vec3| -> Vec3 { Vec3::new(a.x + b.x, a.y + b.y, a.z + b.z) });
+impl_op_ex!(-|a: &Vec3, b: &Vec3| -> Vec3 { Vec3::new(a.x - b.x, a.y - b.y, a.z - b.z) });
+impl_op_ex_commutative!(*|a: &Vec3, b: &f64| -> Vec3 { Vec3::new(a.x * b, a.y * b, a.z * b) });
+impl_op_ex!(/ |a: &Vec3, b: &f64| -> Vec3 { Vec3::new(a.x / b, a.y / b, a.z / b) });
+impl_op_ex!(-|a: &Vec3| -> Vec3 { Vec3::new(-a.x, -a.y, -a.z) });
+
+/// A line (or a ray/trajectory, if you only care about t >= 0) through
+/// `origin` along `direction`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line3 {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+impl Line3 {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Line3 { origin, direction }
+    }
+
+    pub fn at(&self, t: f64) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// Where the two lines cross when projected onto the XY plane, as the
+    /// parameters along each line. None if they're parallel there.
+    pub fn intersect_xy(&self, other: &Self) -> Option<(f64, f64)> {
+        let (d1, d2) = (self.direction, other.direction);
+        let det = d1.x * d2.y - d1.y * d2.x;
+        if det.abs() < EPSILON {
+            return None;
+        }
+        let delta = other.origin - self.origin;
+        let t = (delta.x * d2.y - delta.y * d2.x) / det;
+        let u = (delta.x * d1.y - delta.y * d1.x) / det;
+        Some((t, u))
+    }
+
+    /// The parameters along each line of their closest approach in 3D, or
+    /// None if the lines are parallel.
+    pub fn closest_approach(&self, other: &Self) -> Option<(f64, f64)> {
+        let (d1, d2) = (self.direction, other.direction);
+        let n = d1.cross(&d2);
+        let denom = n.dot(&n);
+        if denom < EPSILON {
+            return None;
+        }
+        let delta = other.origin - self.origin;
+        let t = delta.cross(&d2).dot(&n) / denom;
+        let u = delta.cross(&d1).dot(&n) / denom;
+        Some((t, u))
+    }
+
+    /// The point where the two lines actually meet in 3D, if they do.
+    pub fn intersect(&self, other: &Self) -> Option<Vec3> {
+        let (t, u) = self.closest_approach(other)?;
+        let (a, b) = (self.at(t), other.at(u));
+        match (a - b).length() < 1e-6 {
+            true => Some(a),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::IPoint3;
+    use itertools::Itertools;
+
+    #[test]
+    fn should_bound_and_intersect() {
+        let a = Cuboid::new(IPoint3::new(2, 2, 2), IPoint3::new(0, 0, 0));
+        assert_eq!(IPoint3::new(0, 0, 0), a.min);
+        assert_eq!(27, a.volume());
+        let b = Cuboid::new(IPoint3::new(2, 1, 1), IPoint3::new(5, 5, 5));
+        assert!(a.intersects(&b));
+        assert_eq!(
+            Some(Cuboid::new(IPoint3::new(2, 1, 1), IPoint3::new(2, 2, 2))),
+            a.intersection(&b)
+        );
+        let c = Cuboid::new(IPoint3::new(3, 0, 0), IPoint3::new(4, 2, 2));
+        assert!(!a.intersects(&c));
+        assert!(a.contains(&IPoint3::new(1, 2, 0)));
+        assert!(!a.contains(&IPoint3::new(1, 3, 0)));
+        let bounds = Cuboid::bounding([IPoint3::new(1, 5, -1), IPoint3::new(3, 0, 2)]);
+        assert_eq!(
+            Some(Cuboid::new(IPoint3::new(1, 0, -1), IPoint3::new(3, 5, 2))),
+            bounds
+        );
+    }
+
+    #[test]
+    fn should_get_n_dimensional_neighbors() {
+        let p = PointN([0, 0, 0, 0]);
+        assert_eq!(8, p.neighbors().count());
+        assert_eq!(80, p.neighbors_diagonal().unique().count());
+        assert_eq!(PointN([1, 2]), PointN([3, 3]) - PointN([2, 1]));
+        assert_eq!(4, PointN([1, -1]).manhattan(&PointN([-1, 1])));
+    }
+
+    #[test]
+    fn should_intersect_lines() {
+        // Two of the hailstones from 2023 day 24's example
+        let a = Line3::new(Vec3::new(19., 13., 30.), Vec3::new(-2., 1., -2.));
+        let b = Line3::new(Vec3::new(18., 19., 22.), Vec3::new(-1., -1., -2.));
+        let (t, _) = a.intersect_xy(&b).unwrap();
+        let hit = a.at(t);
+        assert!((hit.x - 14.333).abs() < 1e-3);
+        assert!((hit.y - 15.333).abs() < 1e-3);
+
+        let x = Line3::new(Vec3::new(0., 0., 0.), Vec3::new(1., 1., 1.));
+        let y = Line3::new(Vec3::new(2., 0., 2.), Vec3::new(0., 1., 0.));
+        assert_eq!(Some(Vec3::new(2., 2., 2.)), x.intersect(&y));
+        let z = Line3::new(Vec3::new(2., 0., 3.), Vec3::new(0., 1., 0.));
+        assert_eq!(None, x.intersect(&z));
+        assert_eq!(None, x.closest_approach(&x));
+    }
+}
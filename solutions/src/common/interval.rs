@@ -0,0 +1,331 @@
+// Ranges of numbers, sets of them, and boxes made out of them. Everything is
+// half-open ([start, end)) so that splitting never needs a +1 or -1.
+
+use super::num::Integer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T = i64> {
+    pub start: T,
+    pub end: T,
+}
+impl<T: Integer> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Interval { start, end }
+    }
+
+    /// Builds the interval from inclusive bounds, i.e. `first..=last`.
+    pub fn inclusive(first: T, last: T) -> Self {
+        Interval::new(first, last + T::ONE)
+    }
+
+    pub fn len(&self) -> T {
+        match self.is_empty() {
+            true => T::ZERO,
+            false => self.end - self.start,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, n: T) -> bool {
+        self.start <= n && n < self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end)).non_empty()
+    }
+
+    /// What's left of this interval before and after `other` is taken out.
+    pub fn difference(&self, other: &Self) -> (Option<Self>, Option<Self>) {
+        let before = Interval::new(self.start, self.end.min(other.start));
+        let after = Interval::new(self.start.max(other.end), self.end);
+        (before.non_empty(), after.non_empty())
+    }
+
+    /// Splits into the parts below `at` and from `at` onwards.
+    pub fn split_at(&self, at: T) -> (Option<Self>, Option<Self>) {
+        let below = Interval::new(self.start, self.end.min(at));
+        let above = Interval::new(self.start.max(at), self.end);
+        (below.non_empty(), above.non_empty())
+    }
+
+    fn non_empty(self) -> Option<Self> {
+        match self.is_empty() {
+            true => None,
+            false => Some(self),
+        }
+    }
+}
+
+/// A set of numbers stored as sorted, disjoint, non-touching intervals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet<T = i64>(Vec<Interval<T>>);
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet(vec![])
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        self.0.push(interval);
+        self.normalize();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.0.iter()
+    }
+
+    /// How many numbers are in the set.
+    pub fn len(&self) -> T {
+        self.0.iter().fold(T::ZERO, |acc, i| acc + i.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, n: T) -> bool {
+        self.0.iter().any(|i| i.contains(n))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.0.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.0.last().map(|i| i.end - T::ONE)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.0.iter().chain(other.0.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.0
+            .iter()
+            .flat_map(|a| other.0.iter().filter_map(|b| a.intersection(b)))
+            .collect()
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut remaining = vec![];
+        for a in &self.0 {
+            // `other` is sorted, so anything cut off before one of its
+            // intervals can't be touched by the later ones
+            let mut rest = Some(*a);
+            for b in &other.0 {
+                let Some(r) = rest else { break };
+                let (before, after) = r.difference(b);
+                remaining.extend(before);
+                rest = after;
+            }
+            remaining.extend(rest);
+        }
+        remaining.into_iter().collect()
+    }
+
+    /// Sends every number through the mapping. Anything not covered by one of
+    /// its pieces stays where it is.
+    pub fn map(&self, mapping: &PiecewiseMap<T>) -> Self {
+        let mut unmapped = self.clone();
+        let mut mapped = vec![];
+        for &(source, dest) in &mapping.pieces {
+            let source_set = IntervalSet::from(source);
+            for hit in unmapped.intersection(&source_set).iter() {
+                // Subtract first so unsigned types can't underflow
+                mapped.push(Interval::new(
+                    hit.start - source.start + dest,
+                    hit.end - source.start + dest,
+                ));
+            }
+            unmapped = unmapped.difference(&source_set);
+        }
+        mapped.into_iter().chain(unmapped.0).collect()
+    }
+
+    fn normalize(&mut self) {
+        self.0.retain(|i| !i.is_empty());
+        self.0.sort();
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(self.0.len());
+        for i in self.0.drain(..) {
+            match merged.last_mut() {
+                Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+                _ => merged.push(i),
+            }
+        }
+        self.0 = merged;
+    }
+}
+impl<T: Integer> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet(iter.into_iter().collect());
+        set.normalize();
+        set
+    }
+}
+impl<T: Integer> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        IntervalSet::from_iter([interval])
+    }
+}
+
+/// Moves numbers in each source interval so that its start lands on the
+/// paired destination. Where sources overlap the first one added wins.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PiecewiseMap<T = i64> {
+    pieces: Vec<(Interval<T>, T)>,
+}
+impl<T: Integer> PiecewiseMap<T> {
+    pub fn new() -> Self {
+        PiecewiseMap { pieces: vec![] }
+    }
+
+    pub fn add(&mut self, source: Interval<T>, dest: T) {
+        self.pieces.push((source, dest));
+    }
+
+    /// Maps a single number; unmapped numbers come back unchanged.
+    pub fn apply(&self, n: T) -> T {
+        self.pieces
+            .iter()
+            .find(|(source, _)| source.contains(n))
+            .map_or(n, |&(source, dest)| n - source.start + dest)
+    }
+}
+impl<T: Integer> FromIterator<(Interval<T>, T)> for PiecewiseMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Interval<T>, T)>>(iter: I) -> Self {
+        PiecewiseMap {
+            pieces: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// A box in N dimensions, one interval per axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hyperrectangle<T, const N: usize>(pub [Interval<T>; N]);
+impl<T: Integer, const N: usize> Hyperrectangle<T, N> {
+    pub fn new(axes: [Interval<T>; N]) -> Self {
+        Hyperrectangle(axes)
+    }
+
+    pub fn volume(&self) -> T {
+        self.0.iter().fold(T::ONE, |acc, i| acc * i.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().any(|i| i.is_empty())
+    }
+
+    pub fn contains(&self, point: &[T; N]) -> bool {
+        self.0.iter().zip(point).all(|(i, &n)| i.contains(n))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.0;
+        for (axis, o) in axes.iter_mut().zip(other.0.iter()) {
+            *axis = axis.intersection(o)?;
+        }
+        Some(Hyperrectangle(axes))
+    }
+
+    /// Cuts the box along `axis`, into the part below `at` and the part from
+    /// `at` onwards.
+    pub fn split(&self, axis: usize, at: T) -> (Option<Self>, Option<Self>) {
+        let (below, above) = self.0[axis].split_at(at);
+        let with_axis = |interval: Interval<T>| {
+            let mut next = *self;
+            next.0[axis] = interval;
+            next
+        };
+        (below.map(with_axis), above.map(with_axis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals
+            .iter()
+            .map(|&(s, e)| Interval::new(s, e))
+            .collect()
+    }
+
+    #[test]
+    fn should_split_intervals() {
+        let i = Interval::new(2, 8);
+        assert_eq!(6, i.len());
+        assert_eq!(Interval::new(1, 4), Interval::inclusive(1, 3));
+        assert_eq!(
+            (Some(Interval::new(2, 5)), Some(Interval::new(5, 8))),
+            i.split_at(5)
+        );
+        assert_eq!((None, Some(i)), i.split_at(0));
+        assert_eq!(
+            (Some(Interval::new(2, 3)), Some(Interval::new(6, 8))),
+            i.difference(&Interval::new(3, 6))
+        );
+        assert_eq!(None, i.intersection(&Interval::new(8, 10)));
+    }
+
+    #[test]
+    fn should_normalize_sets() {
+        let s = set(&[(5, 7), (1, 3), (3, 4), (6, 9), (10, 10)]);
+        assert_eq!(set(&[(1, 4), (5, 9)]).0, s.0);
+        assert_eq!(7, s.len());
+        assert_eq!(Some(1), s.min());
+        assert_eq!(Some(8), s.max());
+        assert!(s.contains(3));
+        assert!(!s.contains(4));
+    }
+
+    #[test]
+    fn should_combine_sets() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+        assert_eq!(set(&[(0, 30)]), a.union(&b));
+        assert_eq!(set(&[(5, 10), (20, 25)]), a.intersection(&b));
+        assert_eq!(set(&[(0, 5), (25, 30)]), a.difference(&b));
+        assert_eq!(set(&[(10, 20)]), b.difference(&a));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn should_map_sets() {
+        let mapping: PiecewiseMap<usize> =
+            [(Interval::new(98, 100), 50), (Interval::new(50, 98), 52)]
+                .into_iter()
+                .collect();
+        assert_eq!(81, mapping.apply(79));
+        assert_eq!(14, mapping.apply(14));
+        let seeds = IntervalSet::from(Interval::new(45, 100));
+        let expected: IntervalSet<usize> = [
+            Interval::new(45, 50),
+            Interval::new(50, 52),
+            Interval::new(52, 100),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(expected, seeds.map(&mapping));
+        assert_eq!(55, seeds.map(&mapping).len());
+    }
+
+    #[test]
+    fn should_split_hyperrectangles() {
+        let cube = Hyperrectangle::new([Interval::new(0, 10); 3]);
+        assert_eq!(1000, cube.volume());
+        let (below, above) = cube.split(1, 4);
+        assert_eq!(400, below.unwrap().volume());
+        assert_eq!(600, above.unwrap().volume());
+        assert!(above.unwrap().contains(&[0, 4, 9]));
+        assert_eq!(None, cube.split(2, 10).1);
+        let other = Hyperrectangle::new([Interval::new(5, 15); 3]);
+        assert_eq!(125, cube.intersection(&other).unwrap().volume());
+    }
+}
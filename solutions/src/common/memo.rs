@@ -0,0 +1,167 @@
+// A cache for pure recursive functions. Thread it through the recursion and
+// wrap each call's body in `get_or_insert_with`.
+
+use std::{collections::HashMap, fmt::Display, hash::Hash, iter::Sum, ops::Add};
+
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    limit: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo {
+            cache: HashMap::new(),
+            limit: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// A cache that stops remembering new results once it holds `limit` of
+    /// them. Whatever's already in there is kept.
+    pub fn with_limit(limit: usize) -> Self {
+        Memo {
+            limit: Some(limit),
+            ..Memo::new()
+        }
+    }
+
+    /// Returns the cached value for `key`, or works it out with `f` and
+    /// caches that. `f` gets the memo back so that it can recurse.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.get(&key) {
+            return v;
+        }
+        let v = f(self);
+        self.insert(key, v.clone());
+        v
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let found = self.cache.get(key).cloned();
+        match found {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        found
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.limit.is_some_and(|l| self.cache.len() >= l) {
+            return;
+        }
+        self.cache.insert(key, value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Empties the cache, keeping the running statistics.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            size: self.cache.len(),
+        }
+    }
+}
+impl<K: Hash + Eq, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub size: usize,
+}
+impl MemoStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+impl Add for MemoStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        MemoStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            size: self.size + other.size,
+        }
+    }
+}
+// For totting up a lot of short-lived memos
+impl Sum for MemoStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(MemoStats::default(), Add::add)
+    }
+}
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}%), {} cached",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.,
+            self.size
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.get_or_insert_with(n, |memo| match n {
+            0 | 1 => n,
+            _ => fib(memo, n - 1) + fib(memo, n - 2),
+        })
+    }
+
+    #[test]
+    fn should_memoize() {
+        let mut memo = Memo::new();
+        assert_eq!(12586269025, fib(&mut memo, 50));
+        let stats = memo.stats();
+        assert_eq!(51, stats.misses);
+        assert_eq!(48, stats.hits);
+        assert_eq!(51, stats.size);
+        fib(&mut memo, 50);
+        assert_eq!(49, memo.stats().hits);
+        let total: MemoStats = [stats, memo.stats()].into_iter().sum();
+        assert_eq!((97, 102), (total.hits, total.size));
+    }
+
+    #[test]
+    fn should_respect_limit() {
+        let mut memo = Memo::with_limit(10);
+        assert_eq!(832040, fib(&mut memo, 30));
+        assert_eq!(10, memo.len());
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(
+            "0 hits, 0 misses (0.0%), 0 cached",
+            Memo::<u8, u8>::new().stats().to_string()
+        );
+    }
+}
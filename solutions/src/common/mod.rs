@@ -0,0 +1,235 @@
+// Shared building blocks; not every day uses all of them.
+
+use anyhow::{anyhow, Result};
+use std::str::FromStr;
+
+pub mod cycle;
+#[cfg(test)]
+pub mod differential;
+pub mod geometry;
+pub mod interval;
+pub mod memo;
+pub mod num;
+pub mod poly;
+pub mod polygon;
+pub mod scanner;
+pub mod search;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+impl Direction {
+    /// Clockwise, starting from up.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::Left | Direction::Right)
+    }
+
+    /// The unit step in this direction, with y increasing downwards.
+    pub fn offset(&self) -> IPoint {
+        match self {
+            Direction::Up => IPoint::new(0, -1),
+            Direction::Down => IPoint::new(0, 1),
+            Direction::Left => IPoint::new(-1, 0),
+            Direction::Right => IPoint::new(1, 0),
+        }
+    }
+
+    /// The arrow pointing this way, as accepted by `from_char`.
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+
+    /// Accepts the letter, compass point, or arrow form of a direction.
+    pub fn from_char(c: char) -> Result<Self> {
+        match c {
+            'U' | 'N' | '^' => Ok(Direction::Up),
+            'D' | 'S' | 'v' => Ok(Direction::Down),
+            'L' | 'W' | '<' => Ok(Direction::Left),
+            'R' | 'E' | '>' => Ok(Direction::Right),
+            _ => Err(anyhow!("Invalid direction: {c}")),
+        }
+    }
+}
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self> {
+        Direction::from_char(value)
+    }
+}
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::from_char(c),
+            _ => Err(anyhow!("Invalid direction: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point<T = usize> {
+    pub x: T,
+    pub y: T,
+}
+pub type IPoint = Point<isize>;
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+
+    /// Converts the coordinates to another integer type, failing if either
+    /// doesn't fit (e.g. a negative coordinate into a `Point<usize>`).
+    pub fn try_cast<U: TryFrom<T>>(self) -> Result<Point<U>, U::Error> {
+        Ok(Point::new(U::try_from(self.x)?, U::try_from(self.y)?))
+    }
+}
+impl<T> From<(T, T)> for Point<T> {
+    fn from(value: (T, T)) -> Self {
+        Point::new(value.0, value.1)
+    }
+}
+impl TryFrom<IPoint> for Point {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: IPoint) -> Result<Self, Self::Error> {
+        value.try_cast()
+    }
+}
+impl TryFrom<Point> for IPoint {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: Point) -> Result<Self, Self::Error> {
+        value.try_cast()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3<T = usize> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+pub type IPoint3 = Point3<isize>;
+impl<T> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+
+    pub fn try_cast<U: TryFrom<T>>(self) -> Result<Point3<U>, U::Error> {
+        Ok(Point3::new(
+            U::try_from(self.x)?,
+            U::try_from(self.y)?,
+            U::try_from(self.z)?,
+        ))
+    }
+}
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from(value: (T, T, T)) -> Self {
+        Point3::new(value.0, value.1, value.2)
+    }
+}
+impl<T: FromStr> FromStr for Point3<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    type Err = anyhow::Error;
+
+    /// Parses the usual "x,y,z" form (whitespace around coordinates is fine).
+    fn from_str(s: &str) -> Result<Self> {
+        let coords: Vec<T> = s
+            .split(',')
+            .map(|c| c.trim().parse())
+            .collect::<Result<_, _>>()?;
+        match <[T; 3]>::try_from(coords) {
+            Ok([x, y, z]) => Ok(Point3::new(x, y, z)),
+            Err(_) => Err(anyhow!("Invalid 3D point: {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_turn() {
+        for dir in Direction::ALL {
+            assert_eq!(dir, dir.turn_left().turn_right());
+            assert_eq!(dir.opposite(), dir.turn_left().turn_left());
+            assert_eq!(dir.opposite(), dir.turn_right().turn_right());
+        }
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+    }
+
+    #[test]
+    fn should_parse_directions() -> Result<()> {
+        assert_eq!(Direction::Up, Direction::from_char('N')?);
+        assert_eq!(Direction::Down, Direction::from_char('v')?);
+        assert_eq!(Direction::Left, "L".parse()?);
+        assert_eq!(Direction::Right, Direction::try_from('>')?);
+        assert!(Direction::from_char('x').is_err());
+        assert!("UD".parse::<Direction>().is_err());
+        for dir in Direction::ALL {
+            assert_eq!(dir, Direction::from_char(dir.arrow())?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn should_cast_points() {
+        let unsigned: Result<Point, _> = IPoint::new(3, 4).try_into();
+        assert_eq!(Ok(Point::new(3, 4)), unsigned);
+        let negative: Result<Point, _> = IPoint::new(-1, 4).try_into();
+        assert!(negative.is_err());
+        let signed: Result<IPoint, _> = Point::new(1usize, 2).try_into();
+        assert_eq!(Ok(IPoint::new(1, 2)), signed);
+        let too_big: Result<IPoint, _> = Point::new(usize::MAX, 0).try_into();
+        assert!(too_big.is_err());
+    }
+}
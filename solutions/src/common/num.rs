@@ -0,0 +1,264 @@
+// Still vaguely wondering why these algos aren't in the standard lib tbh
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    /// The absolute value (a no-op for unsigned types).
+    fn magnitude(self) -> Self;
+}
+macro_rules! integer {
+    (unsigned: $($u:ident),*; signed: $($s:ident),*) => {
+        $(impl Integer for $u {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                $u::checked_mul(self, rhs)
+            }
+            fn magnitude(self) -> Self {
+                self
+            }
+        })*
+        $(impl Integer for $s {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                $s::checked_mul(self, rhs)
+            }
+            fn magnitude(self) -> Self {
+                self.abs()
+            }
+        })*
+    };
+}
+integer!(unsigned: u8, u16, u32, u64, u128, usize; signed: i8, i16, i32, i64, i128, isize);
+
+/// Greatest common divisor; always non-negative.
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.magnitude(), b.magnitude());
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, or None if it doesn't fit in `T`. Divides before
+/// multiplying so it only overflows when the answer itself would.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (a.magnitude() / gcd(a, b)).checked_mul(b.magnitude())
+}
+
+/// Least common multiple of every value, or None on overflow. The LCM of
+/// nothing at all is taken to be zero.
+pub fn lcm_all<T: Integer>(values: &[T]) -> Option<T> {
+    match values.split_first() {
+        Some((&first, rest)) => rest
+            .iter()
+            .try_fold(first.magnitude(), |acc, &v| lcm(acc, v)),
+        None => Some(T::ZERO),
+    }
+}
+
+/// Returns (g, x, y) such that a*x + b*y = g = gcd(a, b).
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// (a * b) mod m without overflowing, for any non-negative a, b < m.
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    if let Some(p) = a.checked_mul(b) {
+        return p.rem_euclid(m);
+    }
+    // Too big to multiply directly, so do it the long way
+    let (mut a, mut b, mut result) = (a.rem_euclid(m), b.rem_euclid(m), 0i128);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = ((result as u128 + a as u128) % m as u128) as i128;
+        }
+        a = ((a as u128 * 2) % m as u128) as i128;
+        b >>= 1;
+    }
+    result
+}
+
+/// The x in [0, m) with a*x ≡ 1 (mod m), if a and m are coprime.
+pub fn mod_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    match g {
+        1 => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+/// base^exp mod m, by repeated squaring.
+pub fn mod_pow(base: i128, mut exp: u128, m: i128) -> i128 {
+    if m == 1 {
+        return 0;
+    }
+    let (mut base, mut result) = (base.rem_euclid(m), 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Solves the system x ≡ residue (mod modulus) for every (residue, modulus)
+/// pair, returning (x, lcm of the moduli) with x as small as possible. The
+/// moduli needn't be coprime; None means the congruences contradict each
+/// other (or the combined modulus overflows).
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    let mut acc = (0i128, 1i128);
+    for &(residue, modulus) in congruences {
+        let (r1, m1) = acc;
+        let r2 = residue.rem_euclid(modulus);
+        let (g, p, _) = extended_gcd(m1, modulus);
+        if (r2 - r1) % g != 0 {
+            return None;
+        }
+        let m2g = modulus / g;
+        let combined = (m1 / g).checked_mul(modulus)?;
+        // r1 + m1 * k where k ≡ (r2 - r1)/g * p (mod m2/g)
+        let k = mul_mod(((r2 - r1) / g).rem_euclid(m2g), p.rem_euclid(m2g), m2g);
+        acc = (
+            (r1 + mul_mod(m1, k, combined)).rem_euclid(combined),
+            combined,
+        );
+    }
+    Some(acc)
+}
+
+/// Given cycles that each first hit their target at `offset` and then every
+/// `period` steps after that, finds the first step at which they all line up.
+pub fn align_cycles(cycles: &[(i128, i128)]) -> Option<i128> {
+    let (x, m) = crt(cycles)?;
+    let earliest = cycles.iter().map(|&(offset, _)| offset).max()?;
+    if x >= earliest {
+        return Some(x);
+    }
+    // Bump up by whole periods until every cycle has actually started
+    let periods = (earliest - x + m - 1) / m;
+    Some(x + periods * m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::*};
+
+    #[test]
+    fn should_get_gcd() {
+        assert_eq!(6, gcd(48usize, 18));
+        assert_eq!(6, gcd(-48i64, 18));
+        assert_eq!(5, gcd(0u32, 5));
+    }
+
+    #[test]
+    fn should_get_lcm_without_overflowing() {
+        assert_eq!(Some(12), lcm(4u8, 6));
+        assert_eq!(Some(60), lcm_all(&[3usize, 4, 5, 6]));
+        // a * b alone would overflow here, but the answer fits
+        assert_eq!(Some(u64::MAX - 1), lcm(u64::MAX - 1, (u64::MAX - 1) / 2));
+        assert_eq!(None, lcm_all(&[u8::MAX, 2]));
+        assert_eq!(Some(0), lcm_all::<usize>(&[]));
+    }
+
+    #[test]
+    fn should_get_extended_gcd() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(2, g);
+        assert_eq!(2, 240 * x + 46 * y);
+    }
+
+    #[test]
+    fn should_get_inverse_and_power() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(None, mod_inverse(6, 9));
+        assert_eq!(445, mod_pow(4, 13, 497));
+        assert_eq!(1, mod_pow(2, 1_000_000_006, 1_000_000_007));
+        let big = i128::MAX / 3;
+        assert_eq!(mul_mod(big - 1, 2, big), big - 2);
+    }
+
+    #[test]
+    fn should_solve_crt() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        // Non-coprime moduli that agree...
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        // ...and ones that don't
+        assert_eq!(None, crt(&[(1, 6), (2, 4)]));
+    }
+
+    #[test]
+    fn should_align_cycles() {
+        // Starts at 5 then every 4, and starts at 7 then every 6
+        assert_eq!(Some(13), align_cycles(&[(5, 4), (7, 6)]));
+        // Everything already lines up at the start
+        assert_eq!(Some(0), align_cycles(&[(0, 4), (0, 6)]));
+        assert_eq!(Some(20), align_cycles(&[(20, 4), (8, 6)]));
+    }
+
+    // i64::MIN has no magnitude that fits in an i64, so it's left out
+    proptest! {
+        #[test]
+        fn should_get_a_common_divisor(a in (i64::MIN + 1).., b in (i64::MIN + 1)..) {
+            let g = gcd(a, b);
+            prop_assert_eq!(g, gcd(b, a));
+            prop_assert!(g >= 0);
+            if g != 0 {
+                prop_assert_eq!(0, a % g);
+                prop_assert_eq!(0, b % g);
+            }
+        }
+
+        #[test]
+        fn should_get_lcm_or_overflow(a in (i64::MIN + 1).., b in (i64::MIN + 1)..) {
+            // Worked out again with plenty of room to spare
+            let expected = match a == 0 || b == 0 {
+                true => 0,
+                false => a.unsigned_abs() as i128 / gcd(a, b) as i128 * b.unsigned_abs() as i128,
+            };
+            prop_assert_eq!(i64::try_from(expected).ok(), lcm(a, b));
+        }
+
+        #[test]
+        fn should_get_lcm_divisible_by_everything(values in vec(1..10_000u64, 1..8)) {
+            let wide = lcm_all(&values.iter().map(|&v| v as u128).collect::<Vec<_>>()).unwrap();
+            for &v in &values {
+                prop_assert_eq!(0, wide % v as u128);
+            }
+            prop_assert_eq!(u64::try_from(wide).ok(), lcm_all(&values));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Sequences that come from polynomials: spotting them, fitting them, and
+// running them forwards (or backwards) to wherever the puzzle asks about.
+// Everything is exact, so nothing gets truncated along the way, and anything
+// that would overflow says so (with None) rather than wrapping.
+
+use super::num::gcd;
+use impl_ops::*;
+use std::{fmt::Display, ops};
+
+/// An exact fraction, always kept in lowest terms with a positive denominator.
+///
+/// # Panics
+///
+/// The arithmetic operators panic if the result doesn't fit, or on dividing
+/// by zero. The `checked_*` methods say None instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+impl Rational {
+    /// # Panics
+    ///
+    /// If `den` is zero, or either is `i128::MIN` (which has no positive
+    /// counterpart to reduce with). `checked_new` says None instead.
+    pub fn new(num: i128, den: i128) -> Self {
+        Rational::checked_new(num, den).expect("Rational with a zero denominator or out of range")
+    }
+
+    pub fn checked_new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 || num == i128::MIN || den == i128::MIN {
+            return None;
+        }
+        let g = gcd(num, den).max(1) * den.signum();
+        Some(Rational {
+            num: num / g,
+            den: den / g,
+        })
+    }
+
+    pub fn checked_add(self, rhs: Rational) -> Option<Self> {
+        let num = self
+            .num
+            .checked_mul(rhs.den)?
+            .checked_add(rhs.num.checked_mul(self.den)?)?;
+        Rational::checked_new(num, self.den.checked_mul(rhs.den)?)
+    }
+
+    pub fn checked_sub(self, rhs: Rational) -> Option<Self> {
+        let num = self
+            .num
+            .checked_mul(rhs.den)?
+            .checked_sub(rhs.num.checked_mul(self.den)?)?;
+        Rational::checked_new(num, self.den.checked_mul(rhs.den)?)
+    }
+
+    pub fn checked_mul(self, rhs: Rational) -> Option<Self> {
+        Rational::checked_new(
+            self.num.checked_mul(rhs.num)?,
+            self.den.checked_mul(rhs.den)?,
+        )
+    }
+
+    /// None if `rhs` is zero, as well as if the result doesn't fit.
+    pub fn checked_div(self, rhs: Rational) -> Option<Self> {
+        Rational::checked_new(
+            self.num.checked_mul(rhs.den)?,
+            self.den.checked_mul(rhs.num)?,
+        )
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    /// The value as a whole number, if it is one.
+    pub fn to_integer(self) -> Option<i128> {
+        match self.den {
+            1 => Some(self.num),
+            _ => None,
+        }
+    }
+}
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational { num: n, den: 1 }
+    }
+}
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            _ => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
+}
+impl_op_ex!(+|a: &Rational, b: &Rational| -> Rational {
+    a.checked_add(*b).expect("Rational overflowed")
+});
+impl_op_ex!(-|a: &Rational, b: &Rational| -> Rational {
+    a.checked_sub(*b).expect("Rational overflowed")
+});
+impl_op_ex!(*|a: &Rational, b: &Rational| -> Rational {
+    a.checked_mul(*b).expect("Rational overflowed")
+});
+impl_op_ex!(/|a: &Rational, b: &Rational| -> Rational {
+    a.checked_div(*b).expect("Rational divided by zero or overflowed")
+});
+
+/// The value at `x` of the lowest-degree polynomial through every point.
+/// None if two of the points share an x (so there's no such polynomial), or
+/// if the working doesn't fit in an i128.
+pub fn lagrange(points: &[(i128, i128)], x: i128) -> Option<Rational> {
+    let mut total = Rational::from(0);
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let mut term = Rational::from(yi);
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i != j {
+                let factor = Rational::checked_new(x.checked_sub(xj)?, xi.checked_sub(xj)?)?;
+                term = term.checked_mul(factor)?;
+            }
+        }
+        total = total.checked_add(term)?;
+    }
+    Some(total)
+}
+
+/// The forward difference table: the values themselves, then the differences
+/// between neighbours, then the differences of those, down to a single value.
+/// None if any of the differences don't fit in an i128.
+pub fn differences(values: &[i128]) -> Option<Vec<Vec<i128>>> {
+    let mut table = vec![values.to_vec()];
+    while let Some(last) = table.last().filter(|row| row.len() > 1) {
+        let next = last
+            .windows(2)
+            .map(|w| w[1].checked_sub(w[0]))
+            .collect::<Option<_>>()?;
+        table.push(next);
+    }
+    Some(table)
+}
+
+/// The degree of the polynomial generating the sequence, if there are enough
+/// values to be sure of it (i.e. at least one more than the degree needs).
+/// There never are for an empty sequence, or one too big to take the
+/// differences of.
+pub fn degree(values: &[i128]) -> Option<usize> {
+    if values.is_empty() {
+        return None;
+    }
+    differences(values)?
+        .iter()
+        .position(|row| row.iter().all(|&v| v == 0))
+        .map(|zero| zero.saturating_sub(1))
+}
+
+/// The value at index `n` of the sequence (indices start at zero and can go
+/// negative), by Newton's forward difference formula. With nothing to go
+/// on, that's zero. None if the answer (or the working) doesn't fit in an
+/// i128.
+pub fn extrapolate(values: &[i128], n: i128) -> Option<i128> {
+    if values.is_empty() {
+        return Some(0);
+    }
+    let mut total: i128 = 0;
+    // C(n, k), built up one k at a time. Every intermediate product is
+    // divisible by k + 1, so this stays exact even for negative n.
+    let mut binomial: i128 = 1;
+    for (k, row) in differences(values)?.iter().enumerate() {
+        total = total.checked_add(binomial.checked_mul(row[0])?)?;
+        binomial = binomial.checked_mul(n.checked_sub(k as i128)?)? / (k as i128 + 1);
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_do_rational_arithmetic() {
+        let half = Rational::new(2, 4);
+        assert_eq!(Rational::new(1, 2), half);
+        assert_eq!(Rational::new(-1, 3), Rational::new(2, -6));
+        assert_eq!(Some(1), (half + half).to_integer());
+        assert_eq!(Rational::new(1, 6), half - Rational::new(1, 3));
+        assert_eq!(Rational::new(3, 8), half * Rational::new(3, 4));
+        assert_eq!(Rational::from(2), half / Rational::new(1, 4));
+        assert_eq!("-1/3", Rational::new(1, -3).to_string());
+        assert_eq!(None, half.to_integer());
+        assert_eq!(None, Rational::checked_new(1, 0));
+        assert_eq!(None, half.checked_div(Rational::from(0)));
+        assert_eq!(None, Rational::from(i128::MAX).checked_add(half + half));
+    }
+
+    #[test]
+    fn should_interpolate() {
+        // y = x^2 / 2, which needs fractions in between the integer points
+        let points = [(0, 0), (2, 2), (4, 8)];
+        assert_eq!(Some(Rational::new(1, 2)), lagrange(&points, 1));
+        assert_eq!(Some(Rational::from(50)), lagrange(&points, 10));
+        assert_eq!(Some(Rational::new(9, 2)), lagrange(&points, -3));
+        // Two values for the same x can't both be on it
+        assert_eq!(None, lagrange(&[(1, 1), (1, 2)], 0));
+        assert_eq!(None, lagrange(&[(0, 0), (1, i128::MAX)], 2));
+    }
+
+    #[test]
+    fn should_detect_degree() {
+        assert_eq!(Some(0), degree(&[5, 5, 5]));
+        assert_eq!(Some(1), degree(&[0, 3, 6, 9, 12, 15]));
+        assert_eq!(Some(2), degree(&[1, 3, 6, 10, 15, 21]));
+        assert_eq!(Some(3), degree(&[10, 13, 16, 21, 30, 45]));
+        // Not enough to tell whether these are cubic
+        assert_eq!(None, degree(&[1, 8, 27, 64]));
+        assert_eq!(Some(0), degree(&[0, 0]));
+        assert_eq!(None, degree(&[]));
+        assert_eq!(None, degree(&[i128::MIN, i128::MAX]));
+    }
+
+    #[test]
+    fn should_extrapolate() {
+        assert_eq!(Some(18), extrapolate(&[0, 3, 6, 9, 12, 15], 6));
+        assert_eq!(Some(28), extrapolate(&[1, 3, 6, 10, 15, 21], 6));
+        assert_eq!(Some(68), extrapolate(&[10, 13, 16, 21, 30, 45], 6));
+        assert_eq!(Some(5), extrapolate(&[10, 13, 16, 21, 30, 45], -1));
+        // Squares from three of them, a long way out
+        assert_eq!(Some(1_000_000_000_000), extrapolate(&[0, 1, 4], 1_000_000));
+        assert_eq!(
+            Some(1_000_000),
+            lagrange(&[(0, 0), (1, 1), (2, 4)], 1000).and_then(Rational::to_integer)
+        );
+        assert_eq!(Some(0), extrapolate(&[], 3));
+        // Too far out to fit
+        assert_eq!(None, extrapolate(&[0, 1, 4], i128::MAX / 2));
+    }
+}
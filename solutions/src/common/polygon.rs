@@ -0,0 +1,161 @@
+// Simple polygons on the integer grid. Vertices go round in order (either way)
+// and the last one joins back up to the first.
+
+use super::{num::gcd, IPoint};
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<IPoint>,
+}
+impl Polygon {
+    pub fn new(vertices: Vec<IPoint>) -> Self {
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[IPoint] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (IPoint, IPoint)> + '_ {
+        self.vertices.iter().copied().circular_tuple_windows()
+    }
+
+    /// Twice the enclosed area, by the shoelace formula. Always a whole number
+    /// since the vertices are.
+    pub fn double_area(&self) -> usize {
+        self.edges()
+            .map(|(a, b)| a.x * b.y - a.y * b.x)
+            .sum::<isize>()
+            .unsigned_abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.double_area() as f64 / 2.
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| ((a.x - b.x) as f64).hypot((a.y - b.y) as f64))
+            .sum()
+    }
+
+    /// How many grid points lie on the edges.
+    pub fn boundary_points(&self) -> usize {
+        self.edges()
+            .map(|(a, b)| gcd(a.x - b.x, a.y - b.y).unsigned_abs())
+            .sum()
+    }
+
+    /// How many grid points lie strictly inside, by Pick's theorem
+    /// (A = I + B/2 - 1). A polygon with no area (one that doubles back on
+    /// itself, say) has none, where the formula would go negative.
+    pub fn interior_points(&self) -> usize {
+        match self.double_area() {
+            0 => 0,
+            twice => (twice + 2).saturating_sub(self.boundary_points()) / 2,
+        }
+    }
+
+    /// Every grid point inside or on the edges.
+    pub fn lattice_points(&self) -> usize {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn on_boundary(&self, p: &IPoint) -> bool {
+        self.edges().any(|(a, b)| {
+            let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+            cross == 0
+                && a.x.min(b.x) <= p.x
+                && p.x <= a.x.max(b.x)
+                && a.y.min(b.y) <= p.y
+                && p.y <= a.y.max(b.y)
+        })
+    }
+
+    /// Whether the point is inside the polygon or on one of its edges.
+    pub fn contains(&self, p: &IPoint) -> bool {
+        if self.on_boundary(p) {
+            return true;
+        }
+        // Count how many edges a ray heading right from the point crosses
+        let crossings = self
+            .edges()
+            .filter(|(a, b)| (a.y > p.y) != (b.y > p.y))
+            .filter(|(a, b)| {
+                let lhs = (p.x - a.x) * (b.y - a.y);
+                let rhs = (p.y - a.y) * (b.x - a.x);
+                match b.y > a.y {
+                    true => lhs < rhs,
+                    false => lhs > rhs,
+                }
+            })
+            .count();
+        crossings % 2 == 1
+    }
+}
+impl From<Vec<IPoint>> for Polygon {
+    fn from(vertices: Vec<IPoint>) -> Self {
+        Polygon::new(vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(isize, isize)]) -> Polygon {
+        points.iter().map(|&p| IPoint::from(p)).collect_vec().into()
+    }
+
+    #[test]
+    fn should_measure_squares() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(32, square.double_area());
+        assert_eq!(16., square.area());
+        assert_eq!(16., square.perimeter());
+        assert_eq!(16, square.boundary_points());
+        assert_eq!(9, square.interior_points());
+        assert_eq!(25, square.lattice_points());
+        // Same thing, the other way round
+        let reversed = polygon(&[(0, 4), (4, 4), (4, 0), (0, 0)]);
+        assert_eq!(square.lattice_points(), reversed.lattice_points());
+    }
+
+    #[test]
+    fn should_measure_triangles() {
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 3)]);
+        assert_eq!(6., triangle.area());
+        assert_eq!(12., triangle.perimeter());
+        // The hypotenuse only passes through its own endpoints
+        assert_eq!(8, triangle.boundary_points());
+        assert_eq!(3, triangle.interior_points());
+    }
+
+    #[test]
+    fn should_measure_flat_polygons() {
+        let line = polygon(&[(0, 0), (3, 0)]);
+        assert_eq!(0, line.double_area());
+        assert_eq!(0, line.interior_points());
+        let point = polygon(&[(1, 1)]);
+        assert_eq!(0, point.interior_points());
+    }
+
+    #[test]
+    fn should_contain_points() {
+        // An L shape
+        let l = polygon(&[(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)]);
+        assert!(l.contains(&IPoint::new(1, 1)));
+        assert!(l.contains(&IPoint::new(3, 3)));
+        assert!(!l.contains(&IPoint::new(3, 1)));
+        assert!(!l.contains(&IPoint::new(5, 3)));
+        assert!(l.on_boundary(&IPoint::new(3, 2)));
+        assert!(l.contains(&IPoint::new(3, 2)));
+        assert!(!l.on_boundary(&IPoint::new(1, 1)));
+        let inside = (0..=4)
+            .cartesian_product(0..=4)
+            .filter(|&(x, y)| l.contains(&IPoint::new(x, y)))
+            .count();
+        assert_eq!(l.lattice_points(), inside);
+    }
+}
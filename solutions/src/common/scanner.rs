@@ -0,0 +1,294 @@
+// Looks for lots of fixed strings at once, Aho-Corasick style: a trie of the
+// patterns, plus links saying where to carry on from when the next byte
+// doesn't extend the current match. Every match is reported, even ones that
+// overlap, and the input only needs to be read once, front to back (or back
+// to front).
+
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, VecDeque};
+
+/// A pattern found in the input, covering bytes `start..end`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match<'a, T> {
+    pub start: usize,
+    pub end: usize,
+    pub value: &'a T,
+}
+// Derived, these would need `T: Copy`, which a reference doesn't
+impl<T> Clone for Match<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Match<'_, T> {}
+
+struct Automaton {
+    goto: Vec<HashMap<u8, usize>>,
+    fail: Vec<usize>,
+    /// The patterns ending at each state, including the ones it only gets
+    /// to by failing over. Longest first.
+    outputs: Vec<Vec<usize>>,
+}
+impl Automaton {
+    fn new(patterns: &[Vec<u8>]) -> Self {
+        let mut goto = vec![HashMap::new()];
+        let mut outputs = vec![vec![]];
+        for (id, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &b in pattern {
+                state = match goto[state].get(&b) {
+                    Some(&next) => next,
+                    None => {
+                        goto.push(HashMap::new());
+                        outputs.push(vec![]);
+                        let next = goto.len() - 1;
+                        goto[state].insert(b, next);
+                        next
+                    }
+                };
+            }
+            outputs[state].push(id);
+        }
+
+        // Breadth first, so every state's fail link is already known by the
+        // time its children need it
+        let mut fail = vec![0; goto.len()];
+        let mut queue = goto[0].values().copied().collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            for (&b, &child) in &goto[state] {
+                let mut f = fail[state];
+                while f != 0 && !goto[f].contains_key(&b) {
+                    f = fail[f];
+                }
+                fail[child] = match goto[f].get(&b) {
+                    Some(&next) if next != child => next,
+                    _ => 0,
+                };
+                let inherited = outputs[fail[child]].clone();
+                outputs[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        Automaton {
+            goto,
+            fail,
+            outputs,
+        }
+    }
+
+    fn next(&self, mut state: usize, b: u8) -> usize {
+        loop {
+            if let Some(&next) = self.goto[state].get(&b) {
+                return next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.fail[state];
+        }
+    }
+}
+
+/// Finds any of a fixed set of patterns, each of which stands for a value.
+pub struct Scanner<T> {
+    forward: Automaton,
+    /// The same patterns spelled backwards, for scanning from the end.
+    backward: Automaton,
+    lengths: Vec<usize>,
+    values: Vec<T>,
+}
+impl<T> Scanner<T> {
+    /// Patterns can overlap or even repeat; all of them are reported. Empty
+    /// ones can't be, though, so they're an error.
+    pub fn new<S: AsRef<str>>(patterns: impl IntoIterator<Item = (S, T)>) -> Result<Self> {
+        let (patterns, values): (Vec<S>, Vec<T>) = patterns.into_iter().unzip();
+        if let Some(i) = patterns.iter().position(|p| p.as_ref().is_empty()) {
+            return Err(anyhow!("Pattern {i} is empty"));
+        }
+        let forward = patterns
+            .iter()
+            .map(|p| p.as_ref().bytes().collect())
+            .collect::<Vec<Vec<u8>>>();
+        let backward = forward
+            .iter()
+            .map(|p| p.iter().rev().copied().collect())
+            .collect::<Vec<_>>();
+        Ok(Scanner {
+            forward: Automaton::new(&forward),
+            backward: Automaton::new(&backward),
+            lengths: forward.iter().map(Vec::len).collect(),
+            values,
+        })
+    }
+
+    /// Every match in `input` as it's read, in order of where they end.
+    pub fn scan<I: IntoIterator<Item = u8>>(&self, input: I) -> Matches<'_, T, I::IntoIter> {
+        Matches {
+            scanner: self,
+            input: input.into_iter(),
+            state: 0,
+            read: 0,
+            pending: vec![],
+        }
+    }
+
+    /// Every match in `s`, overlapping ones included.
+    pub fn find_iter<'a>(&'a self, s: &'a str) -> impl Iterator<Item = Match<'a, T>> + 'a {
+        self.scan(s.bytes())
+    }
+
+    /// The match that starts earliest, stopping as soon as nothing later
+    /// could start any sooner.
+    pub fn first(&self, s: &str) -> Option<Match<'_, T>> {
+        let longest = self.lengths.iter().max().copied().unwrap_or(0);
+        let mut best: Option<Match<T>> = None;
+        for m in self.scan(s.bytes()) {
+            if best.is_some_and(|b| m.end >= b.start + longest) {
+                break;
+            }
+            if best.is_none_or(|b| m.start < b.start) {
+                best = Some(m);
+            }
+        }
+        best
+    }
+
+    /// The match that ends latest, reading `s` from the back.
+    pub fn last(&self, s: &str) -> Option<Match<'_, T>> {
+        let longest = self.lengths.iter().max().copied().unwrap_or(0);
+        let mut best: Option<Match<T>> = None;
+        let mut state = 0;
+        for (i, b) in s.bytes().enumerate().rev() {
+            if best.is_some_and(|m| i + longest <= m.end) {
+                break;
+            }
+            state = self.backward.next(state, b);
+            for &id in &self.backward.outputs[state] {
+                let end = i + self.lengths[id];
+                if best.is_none_or(|m| end > m.end) {
+                    best = Some(Match {
+                        start: i,
+                        end,
+                        value: &self.values[id],
+                    });
+                }
+            }
+        }
+        best
+    }
+}
+
+/// The matches from `Scanner::scan`, found as the input is read.
+pub struct Matches<'a, T, I> {
+    scanner: &'a Scanner<T>,
+    input: I,
+    state: usize,
+    read: usize,
+    /// Patterns that end at the last byte read and haven't been returned yet.
+    pending: Vec<usize>,
+}
+impl<'a, T, I: Iterator<Item = u8>> Iterator for Matches<'a, T, I> {
+    type Item = Match<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let b = self.input.next()?;
+            self.read += 1;
+            self.state = self.scanner.forward.next(self.state, b);
+            // Reversed so the longest comes off first
+            self.pending = self.scanner.forward.outputs[self.state].clone();
+            self.pending.reverse();
+        }
+        let id = self.pending.pop()?;
+        Some(Match {
+            start: self.read - self.scanner.lengths[id],
+            end: self.read,
+            value: &self.scanner.values[id],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    fn numbers() -> Result<Scanner<u32>> {
+        Scanner::new([("one", 1), ("two", 2), ("eight", 8), ("nine", 9), ("1", 1)])
+    }
+
+    /// Everywhere any of the patterns can be found in `haystack`, the slow
+    /// way, sorted.
+    fn naive(patterns: &[&str], haystack: &str) -> Vec<(usize, usize)> {
+        (1..=haystack.len())
+            .cartesian_product(patterns)
+            .filter(|(end, p)| haystack[..*end].ends_with(**p))
+            .map(|(end, p)| (end - p.len(), end))
+            .sorted()
+            .collect()
+    }
+
+    #[test]
+    fn should_find_overlapping_matches() -> Result<()> {
+        let scanner = numbers()?;
+        let found = scanner
+            .find_iter("xtwoneight1")
+            .map(|m| (m.start, m.end, *m.value))
+            .collect_vec();
+        assert_eq!(vec![(1, 4, 2), (3, 6, 1), (5, 10, 8), (10, 11, 1)], found);
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_first_and_last() -> Result<()> {
+        let scanner = numbers()?;
+        assert_eq!(Some(&2), scanner.first("xtwoneight").map(|m| m.value));
+        assert_eq!(Some(&8), scanner.last("xtwoneight").map(|m| m.value));
+        assert_eq!(None, scanner.first("nothing here"));
+        assert_eq!(None, scanner.last(""));
+        Ok(())
+    }
+
+    #[test]
+    fn should_prefer_earliest_start_over_earliest_end() -> Result<()> {
+        let scanner = Scanner::new([("abcd", 'l'), ("bc", 's')])?;
+        assert_eq!(Some(&'l'), scanner.first("abcd").map(|m| m.value));
+        assert_eq!(Some(&'l'), scanner.last("abcd").map(|m| m.value));
+        assert_eq!(Some(&'s'), scanner.last("abcdbc").map(|m| m.value));
+        Ok(())
+    }
+
+    #[test]
+    fn should_match_multibyte_patterns() -> Result<()> {
+        let scanner = Scanner::new([("fünf", 5), ("drei", 3)])?;
+        let m = scanner.first("xfünfdrei").unwrap();
+        assert_eq!((1, 6), (m.start, m.end));
+        assert_eq!("fünf", &"xfünfdrei"[m.start..m.end]);
+        assert_eq!(Some(&3), scanner.last("xfünfdrei").map(|m| m.value));
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_empty_patterns() {
+        assert!(Scanner::new([("a", 1), ("", 2)]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn should_find_what_a_naive_search_does(
+            patterns in proptest::collection::vec("[ab]{1,4}", 1..5),
+            haystack in "[abc]{0,20}",
+        ) {
+            let patterns = patterns.iter().map(|p| p.as_str()).collect_vec();
+            let scanner = Scanner::new(patterns.iter().map(|&p| (p, ()))).unwrap();
+            let found = scanner.find_iter(&haystack).map(|m| (m.start, m.end)).collect_vec();
+            let expected = naive(&patterns, &haystack);
+            prop_assert_eq!(&expected, &found.iter().copied().sorted().collect_vec());
+            let first = scanner.first(&haystack).map(|m| m.start);
+            prop_assert_eq!(expected.first().map(|&(start, _)| start), first);
+            let last = scanner.last(&haystack).map(|m| m.end);
+            prop_assert_eq!(expected.iter().map(|&(_, end)| end).max(), last);
+        }
+    }
+}
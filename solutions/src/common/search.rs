@@ -0,0 +1,319 @@
+// Graph searches over any hashable state, given a closure that produces the
+// states reachable from a given one. Nothing here recurses, so big graphs
+// can't blow the stack.
+
+use super::num::Integer;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// A route found by one of the searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C = usize> {
+    /// Total cost of the route (the number of steps, for unweighted searches).
+    pub cost: C,
+    /// Every state along the way, from the start to the goal inclusive.
+    pub states: Vec<S>,
+    /// How many distinct states the search had to look at.
+    pub visited: usize,
+}
+
+// Searches keep every state they discover in one list and refer to them by
+// index, so states only need Hash + Eq (not Ord) and paths can be rebuilt by
+// following parent indices.
+struct Discovered<S> {
+    nodes: Vec<(S, Option<usize>)>,
+}
+impl<S: Clone> Discovered<S> {
+    fn new() -> Self {
+        Discovered { nodes: vec![] }
+    }
+
+    fn add(&mut self, state: S, parent: Option<usize>) -> usize {
+        self.nodes.push((state, parent));
+        self.nodes.len() - 1
+    }
+
+    fn state(&self, index: usize) -> &S {
+        &self.nodes[index].0
+    }
+
+    fn path_to(&self, mut index: usize) -> Vec<S> {
+        let mut path = vec![self.nodes[index].0.clone()];
+        while let Some(parent) = self.nodes[index].1 {
+            path.push(self.nodes[parent].0.clone());
+            index = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Breadth-first search for the fewest steps to any state satisfying `is_goal`.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut discovered = Discovered::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([(discovered.add(start, None), 0)]);
+    while let Some((index, depth)) = queue.pop_front() {
+        if is_goal(discovered.state(index)) {
+            return Some(Path {
+                cost: depth,
+                states: discovered.path_to(index),
+                visited: seen.len(),
+            });
+        }
+        for next in successors(discovered.state(index)) {
+            if seen.insert(next.clone()) {
+                queue.push_back((discovered.add(next, Some(index)), depth + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `start`, with the fewest steps needed to get there.
+pub fn bfs_distances<S, I>(start: S, successors: impl FnMut(&S) -> I) -> HashMap<S, usize>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    bfs_distances_within(start, usize::MAX, successors)
+}
+
+/// Like `bfs_distances`, but stops exploring after `limit` steps. Handy when
+/// the graph goes on forever.
+pub fn bfs_distances_within<S, I>(
+    start: S,
+    limit: usize,
+    mut successors: impl FnMut(&S) -> I,
+) -> HashMap<S, usize>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((state, depth)) = queue.pop_front() {
+        if depth == limit {
+            continue;
+        }
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), depth + 1);
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Depth-first search for any route to a state satisfying `is_goal`. Not
+/// necessarily the shortest one; the cost is just its length.
+pub fn dfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S>>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut discovered = Discovered::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![discovered.add(start, None)];
+    while let Some(index) = stack.pop() {
+        if !seen.insert(discovered.state(index).clone()) {
+            continue;
+        }
+        if is_goal(discovered.state(index)) {
+            let states = discovered.path_to(index);
+            return Some(Path {
+                cost: states.len() - 1,
+                states,
+                visited: seen.len(),
+            });
+        }
+        for next in successors(discovered.state(index)) {
+            if !seen.contains(&next) {
+                stack.push(discovered.add(next, Some(index)));
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `start`, explored depth-first.
+pub fn dfs_reach<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> HashSet<S>
+where
+    S: Clone + Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = HashSet::new();
+    let mut stack = vec![start];
+    while let Some(state) = stack.pop() {
+        if seen.contains(&state) {
+            continue;
+        }
+        stack.extend(successors(&state).into_iter().filter(|s| !seen.contains(s)));
+        seen.insert(state);
+    }
+    seen
+}
+
+/// The cheapest route to a state satisfying `is_goal`, where `successors`
+/// gives each next state along with the cost of moving there.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Integer,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::ZERO, is_goal)
+}
+
+/// Dijkstra, but guided by `heuristic`, which must never overestimate the
+/// remaining cost to a goal or the route found might not be the cheapest.
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Integer,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut discovered = Discovered::new();
+    let mut best = HashMap::from([(start.clone(), C::ZERO)]);
+    let estimate = heuristic(&start);
+    let mut frontier =
+        BinaryHeap::from([Reverse((estimate, C::ZERO, discovered.add(start, None)))]);
+    while let Some(Reverse((_, cost, index))) = frontier.pop() {
+        let state = discovered.state(index);
+        // Already found a cheaper way here since this was queued
+        if best.get(state).is_some_and(|&b| b < cost) {
+            continue;
+        }
+        if is_goal(state) {
+            return Some(Path {
+                cost,
+                states: discovered.path_to(index),
+                visited: best.len(),
+            });
+        }
+        for (next, step) in successors(state) {
+            let next_cost = cost + step;
+            if best.get(&next).is_some_and(|&b| b <= next_cost) {
+                continue;
+            }
+            best.insert(next.clone(), next_cost);
+            let estimate = next_cost + heuristic(&next);
+            frontier.push(Reverse((
+                estimate,
+                next_cost,
+                discovered.add(next, Some(index)),
+            )));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::IPoint;
+
+    const MAZE: &str = "\
+#######
+#S..#.#
+#.#.#.#
+#.#...#
+#...#E#
+#######";
+
+    fn open(maze: &str) -> HashSet<IPoint> {
+        let mut open = HashSet::new();
+        for (y, line) in maze.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c != '#' {
+                    open.insert(IPoint::new(x as isize, y as isize));
+                }
+            }
+        }
+        open
+    }
+
+    #[test]
+    fn should_bfs() {
+        let open = open(MAZE);
+        let (start, end) = (IPoint::new(1, 1), IPoint::new(5, 4));
+        let next = |p: &IPoint| {
+            p.neighbors()
+                .filter(|n| open.contains(n))
+                .collect::<Vec<_>>()
+        };
+        let path = bfs(start, next, |&p| p == end).unwrap();
+        assert_eq!(7, path.cost);
+        assert_eq!(8, path.states.len());
+        assert_eq!(Some(&start), path.states.first());
+        assert_eq!(Some(&end), path.states.last());
+        assert!(path.visited <= open.len());
+        assert_eq!(None, bfs(start, next, |p| p.x == 0));
+
+        let distances = bfs_distances(start, next);
+        assert_eq!(open.len(), distances.len());
+        assert_eq!(Some(&7), distances.get(&end));
+        assert_eq!(5, bfs_distances_within(start, 2, next).len());
+    }
+
+    #[test]
+    fn should_dfs() {
+        let open = open(MAZE);
+        let (start, end) = (IPoint::new(1, 1), IPoint::new(5, 4));
+        let next = |p: &IPoint| {
+            p.neighbors()
+                .filter(|n| open.contains(n))
+                .collect::<Vec<_>>()
+        };
+        let path = dfs(start, next, |&p| p == end).unwrap();
+        assert_eq!(Some(&end), path.states.last());
+        assert!(path.states.windows(2).all(|w| w[0].manhattan(&w[1]) == 1));
+        assert_eq!(open, dfs_reach(start, next));
+    }
+
+    #[test]
+    fn should_find_cheapest_path() {
+        // Going straight through the middle costs more than going around
+        let costs = [[1, 9, 1], [1, 9, 1], [1, 1, 1]];
+        let cost = |p: &IPoint| costs[p.y as usize][p.x as usize];
+        let next = |p: &IPoint| {
+            p.neighbors()
+                .filter(|n| (0..3).contains(&n.x) && (0..3).contains(&n.y))
+                .map(|n| (n, cost(&n)))
+                .collect::<Vec<_>>()
+        };
+        let (start, end) = (IPoint::new(0, 0), IPoint::new(2, 0));
+        let path = dijkstra(start, next, |&p| p == end).unwrap();
+        assert_eq!(6, path.cost);
+        assert_eq!(7, path.states.len());
+
+        let guided = astar(start, next, |p| p.manhattan(&end), |&p| p == end).unwrap();
+        assert_eq!(path.cost, guided.cost);
+        assert!(guided.visited <= path.visited);
+    }
+}
@@ -1,41 +1,374 @@
-// This was getting out of hand to implement inline.
-use crate::common::{IPoint, Point};
+// This was getting out of hand to implement inline. Then it got out of hand
+// to implement by hand, so now a macro stamps it all out per coordinate type.
+// Not every day needs every operation.
+
+use crate::common::{Direction, Point, Point3};
 use impl_ops::*;
 use std::ops;
 
-type PointTuple = (usize, usize);
-type IPointTuple = (isize, isize);
-
-impl_op!(+ |a: Point, b: Point| -> Point { Point::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: Point, b: Point| -> Point { Point::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: Point, b: PointTuple| -> Point { Point::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: Point, b: PointTuple| -> Point { Point::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: Point, b: &Point| -> Point { Point::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: Point, b: &Point| -> Point { Point::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: Point, b: &PointTuple| -> Point { Point::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: Point, b: &PointTuple| -> Point { Point::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: &Point, b: Point| -> Point { Point::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: &Point, b: Point| -> Point { Point::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: &Point, b: PointTuple| -> Point { Point::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: &Point, b: PointTuple| -> Point { Point::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: &Point, b: &Point| -> Point { Point::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: &Point, b: &Point| -> Point { Point::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: &Point, b: &PointTuple| -> Point { Point::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: &Point, b: &PointTuple| -> Point { Point::new(a.x - b.0, a.y - b.1) });
-
-impl_op!(+ |a: IPoint, b: IPoint| -> IPoint { IPoint::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: IPoint, b: IPoint| -> IPoint { IPoint::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: IPoint, b: IPointTuple| -> IPoint { IPoint::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: IPoint, b: IPointTuple| -> IPoint { IPoint::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: IPoint, b: &IPoint| -> IPoint { IPoint::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: IPoint, b: &IPoint| -> IPoint { IPoint::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: IPoint, b: &IPointTuple| -> IPoint { IPoint::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: IPoint, b: &IPointTuple| -> IPoint { IPoint::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: &IPoint, b: IPoint| -> IPoint { IPoint::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: &IPoint, b: IPoint| -> IPoint { IPoint::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: &IPoint, b: IPointTuple| -> IPoint { IPoint::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: &IPoint, b: IPointTuple| -> IPoint { IPoint::new(a.x - b.0, a.y - b.1) });
-impl_op!(+ |a: &IPoint, b: &IPoint| -> IPoint { IPoint::new(a.x + b.x, a.y + b.y) });
-impl_op!(-|a: &IPoint, b: &IPoint| -> IPoint { IPoint::new(a.x - b.x, a.y - b.y) });
-impl_op!(+ |a: &IPoint, b: &IPointTuple| -> IPoint { IPoint::new(a.x + b.0, a.y + b.1) });
-impl_op!(-|a: &IPoint, b: &IPointTuple| -> IPoint { IPoint::new(a.x - b.0, a.y - b.1) });
+// impl_ops only accepts paths, so tuples need a name
+type Pair<T> = (T, T);
+type Triple<T> = (T, T, T);
+
+const CARDINAL: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const SURROUNDING: [(i8, i8); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+const FACES: [(i8, i8, i8); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+// Operators behave exactly like the underlying integers do (i.e. usize
+// subtraction panics in debug and wraps in release); use the checked,
+// wrapping, or saturating methods when that matters.
+macro_rules! point_ops {
+    ($($t:ident => $unsigned:ident),*) => {$(
+        impl_op_ex!(+ |a: &Point<$t>, b: &Point<$t>| -> Point<$t> { Point::new(a.x + b.x, a.y + b.y) });
+        impl_op_ex!(- |a: &Point<$t>, b: &Point<$t>| -> Point<$t> { Point::new(a.x - b.x, a.y - b.y) });
+        impl_op_ex!(+ |a: &Point<$t>, b: &Pair<$t>| -> Point<$t> { Point::new(a.x + b.0, a.y + b.1) });
+        impl_op_ex!(- |a: &Point<$t>, b: &Pair<$t>| -> Point<$t> { Point::new(a.x - b.0, a.y - b.1) });
+        impl_op_ex_commutative!(* |a: &Point<$t>, b: &$t| -> Point<$t> { Point::new(a.x * b, a.y * b) });
+        impl_op_ex!(/ |a: &Point<$t>, b: &$t| -> Point<$t> { Point::new(a.x / b, a.y / b) });
+
+        impl_op_ex!(+= |a: &mut Point<$t>, b: &Point<$t>| { a.x += b.x; a.y += b.y; });
+        impl_op_ex!(-= |a: &mut Point<$t>, b: &Point<$t>| { a.x -= b.x; a.y -= b.y; });
+        impl_op_ex!(+= |a: &mut Point<$t>, b: &Pair<$t>| { a.x += b.0; a.y += b.1; });
+        impl_op_ex!(-= |a: &mut Point<$t>, b: &Pair<$t>| { a.x -= b.0; a.y -= b.1; });
+        impl_op_ex!(*= |a: &mut Point<$t>, b: &$t| { a.x *= b; a.y *= b; });
+        impl_op_ex!(/= |a: &mut Point<$t>, b: &$t| { a.x /= b; a.y /= b; });
+
+        impl Point<$t> {
+            pub fn checked_add(&self, other: &Self) -> Option<Self> {
+                Some(Point::new(self.x.checked_add(other.x)?, self.y.checked_add(other.y)?))
+            }
+
+            pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+                Some(Point::new(self.x.checked_sub(other.x)?, self.y.checked_sub(other.y)?))
+            }
+
+            pub fn wrapping_add(&self, other: &Self) -> Self {
+                Point::new(self.x.wrapping_add(other.x), self.y.wrapping_add(other.y))
+            }
+
+            pub fn wrapping_sub(&self, other: &Self) -> Self {
+                Point::new(self.x.wrapping_sub(other.x), self.y.wrapping_sub(other.y))
+            }
+
+            pub fn saturating_add(&self, other: &Self) -> Self {
+                Point::new(self.x.saturating_add(other.x), self.y.saturating_add(other.y))
+            }
+
+            pub fn saturating_sub(&self, other: &Self) -> Self {
+                Point::new(self.x.saturating_sub(other.x), self.y.saturating_sub(other.y))
+            }
+
+            pub fn manhattan(&self, other: &Self) -> $unsigned {
+                self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+            }
+
+            pub fn chebyshev(&self, other: &Self) -> $unsigned {
+                self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+            }
+
+            /// Wraps the point onto a `width` x `height` grid that repeats
+            /// infinitely in every direction.
+            pub fn rem_euclid(&self, width: $t, height: $t) -> Self {
+                Point::new(self.x.rem_euclid(width), self.y.rem_euclid(height))
+            }
+
+            fn offset_by(&self, dx: i8, dy: i8) -> Option<Self> {
+                let shift = |v: $t, d: i8| match d {
+                    -1 => v.checked_sub(1),
+                    1 => v.checked_add(1),
+                    _ => Some(v),
+                };
+                Some(Point::new(shift(self.x, dx)?, shift(self.y, dy)?))
+            }
+
+            /// One step in the given direction, or None if that would leave
+            /// the coordinate type's range (e.g. going left from x = 0).
+            pub fn step(&self, dir: Direction) -> Option<Self> {
+                let offset = dir.offset();
+                self.offset_by(offset.x as i8, offset.y as i8)
+            }
+
+            /// The four orthogonal neighbors, clockwise from up.
+            pub fn neighbors(&self) -> impl Iterator<Item = Self> {
+                let p = *self;
+                CARDINAL.iter().filter_map(move |&(dx, dy)| p.offset_by(dx, dy))
+            }
+
+            /// All eight surrounding neighbors, clockwise from up.
+            pub fn neighbors_diagonal(&self) -> impl Iterator<Item = Self> {
+                let p = *self;
+                SURROUNDING.iter().filter_map(move |&(dx, dy)| p.offset_by(dx, dy))
+            }
+        }
+    )*};
+}
+
+// Same again in three dimensions; neighbors are the 6 face-adjacent cubes,
+// and the diagonal variant adds edges and corners for all 26.
+macro_rules! point3_ops {
+    ($($t:ident => $unsigned:ident),*) => {$(
+        impl_op_ex!(+ |a: &Point3<$t>, b: &Point3<$t>| -> Point3<$t> { Point3::new(a.x + b.x, a.y + b.y, a.z + b.z) });
+        impl_op_ex!(- |a: &Point3<$t>, b: &Point3<$t>| -> Point3<$t> { Point3::new(a.x - b.x, a.y - b.y, a.z - b.z) });
+        impl_op_ex!(+ |a: &Point3<$t>, b: &Triple<$t>| -> Point3<$t> { Point3::new(a.x + b.0, a.y + b.1, a.z + b.2) });
+        impl_op_ex!(- |a: &Point3<$t>, b: &Triple<$t>| -> Point3<$t> { Point3::new(a.x - b.0, a.y - b.1, a.z - b.2) });
+        impl_op_ex_commutative!(* |a: &Point3<$t>, b: &$t| -> Point3<$t> { Point3::new(a.x * b, a.y * b, a.z * b) });
+        impl_op_ex!(/ |a: &Point3<$t>, b: &$t| -> Point3<$t> { Point3::new(a.x / b, a.y / b, a.z / b) });
+
+        impl_op_ex!(+= |a: &mut Point3<$t>, b: &Point3<$t>| { a.x += b.x; a.y += b.y; a.z += b.z; });
+        impl_op_ex!(-= |a: &mut Point3<$t>, b: &Point3<$t>| { a.x -= b.x; a.y -= b.y; a.z -= b.z; });
+        impl_op_ex!(+= |a: &mut Point3<$t>, b: &Triple<$t>| { a.x += b.0; a.y += b.1; a.z += b.2; });
+        impl_op_ex!(-= |a: &mut Point3<$t>, b: &Triple<$t>| { a.x -= b.0; a.y -= b.1; a.z -= b.2; });
+        impl_op_ex!(*= |a: &mut Point3<$t>, b: &$t| { a.x *= b; a.y *= b; a.z *= b; });
+        impl_op_ex!(/= |a: &mut Point3<$t>, b: &$t| { a.x /= b; a.y /= b; a.z /= b; });
+
+        impl Point3<$t> {
+            pub fn checked_add(&self, other: &Self) -> Option<Self> {
+                Some(Point3::new(
+                    self.x.checked_add(other.x)?,
+                    self.y.checked_add(other.y)?,
+                    self.z.checked_add(other.z)?,
+                ))
+            }
+
+            pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+                Some(Point3::new(
+                    self.x.checked_sub(other.x)?,
+                    self.y.checked_sub(other.y)?,
+                    self.z.checked_sub(other.z)?,
+                ))
+            }
+
+            pub fn wrapping_add(&self, other: &Self) -> Self {
+                Point3::new(
+                    self.x.wrapping_add(other.x),
+                    self.y.wrapping_add(other.y),
+                    self.z.wrapping_add(other.z),
+                )
+            }
+
+            pub fn wrapping_sub(&self, other: &Self) -> Self {
+                Point3::new(
+                    self.x.wrapping_sub(other.x),
+                    self.y.wrapping_sub(other.y),
+                    self.z.wrapping_sub(other.z),
+                )
+            }
+
+            pub fn saturating_add(&self, other: &Self) -> Self {
+                Point3::new(
+                    self.x.saturating_add(other.x),
+                    self.y.saturating_add(other.y),
+                    self.z.saturating_add(other.z),
+                )
+            }
+
+            pub fn saturating_sub(&self, other: &Self) -> Self {
+                Point3::new(
+                    self.x.saturating_sub(other.x),
+                    self.y.saturating_sub(other.y),
+                    self.z.saturating_sub(other.z),
+                )
+            }
+
+            pub fn manhattan(&self, other: &Self) -> $unsigned {
+                self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+            }
+
+            pub fn chebyshev(&self, other: &Self) -> $unsigned {
+                self.x
+                    .abs_diff(other.x)
+                    .max(self.y.abs_diff(other.y))
+                    .max(self.z.abs_diff(other.z))
+            }
+
+            fn offset_by(&self, dx: i8, dy: i8, dz: i8) -> Option<Self> {
+                let shift = |v: $t, d: i8| match d {
+                    -1 => v.checked_sub(1),
+                    1 => v.checked_add(1),
+                    _ => Some(v),
+                };
+                Some(Point3::new(shift(self.x, dx)?, shift(self.y, dy)?, shift(self.z, dz)?))
+            }
+
+            /// The six face-adjacent neighbors.
+            pub fn neighbors(&self) -> impl Iterator<Item = Self> {
+                let p = *self;
+                FACES.iter().filter_map(move |&(dx, dy, dz)| p.offset_by(dx, dy, dz))
+            }
+
+            /// All 26 neighbors sharing a face, edge, or corner.
+            pub fn neighbors_diagonal(&self) -> impl Iterator<Item = Self> {
+                let p = *self;
+                (-1..=1)
+                    .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                    .filter(|&d| d != (0, 0, 0))
+                    .filter_map(move |(dx, dy, dz)| p.offset_by(dx, dy, dz))
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_point_ops {
+    ($($t:ident),*) => {$(
+        impl_op_ex!(- |a: &Point<$t>| -> Point<$t> { Point::new(-a.x, -a.y) });
+        impl_op_ex!(- |a: &Point3<$t>| -> Point3<$t> { Point3::new(-a.x, -a.y, -a.z) });
+    )*};
+}
+
+point_ops!(usize => usize, u64 => u64, u32 => u32, isize => usize, i64 => u64, i32 => u32);
+point3_ops!(usize => usize, u64 => u64, u32 => u32, isize => usize, i64 => u64, i32 => u32);
+signed_point_ops!(isize, i64, i32);
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{IPoint, IPoint3};
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn coordinate() -> impl Strategy<Value = isize> {
+        -1_000_000isize..1_000_000
+    }
+
+    fn ipoint() -> impl Strategy<Value = IPoint> {
+        (coordinate(), coordinate()).prop_map(|(x, y)| IPoint::new(x, y))
+    }
+
+    fn ipoint3() -> impl Strategy<Value = IPoint3> {
+        (coordinate(), coordinate(), coordinate()).prop_map(|(x, y, z)| IPoint3::new(x, y, z))
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)] // the borrowed forms are what's being tested
+    fn should_do_arithmetic() {
+        let mut p = IPoint::new(1, -2);
+        assert_eq!(IPoint::new(3, -1), p + IPoint::new(2, 1));
+        assert_eq!(IPoint::new(3, -1), &p + (2, 1));
+        assert_eq!(IPoint::new(-1, -3), p - &IPoint::new(2, 1));
+        assert_eq!(IPoint::new(3, -6), p * 3);
+        assert_eq!(IPoint::new(3, -6), 3 * p);
+        assert_eq!(IPoint::new(0, -1), p / 2);
+        assert_eq!(IPoint::new(-1, 2), -p);
+        p += (1, 1);
+        p -= IPoint::new(0, 2);
+        p *= 2;
+        assert_eq!(IPoint::new(4, -6), p);
+    }
+
+    #[test]
+    fn should_check_overflow() {
+        let a = Point::new(1usize, 5);
+        let b = Point::new(2usize, 1);
+        assert_eq!(None, a.checked_sub(&b));
+        assert_eq!(Some(Point::new(1, 0)), b.checked_sub(&Point::new(1, 1)));
+        assert_eq!(Point::new(0, 4), a.saturating_sub(&b));
+        assert_eq!(Point::new(usize::MAX, 4), a.wrapping_sub(&b));
+        assert_eq!(None, Point::new(usize::MAX, 0).checked_add(&a));
+    }
+
+    #[test]
+    fn should_measure_distance() {
+        let a = IPoint::new(-2, 3);
+        let b = IPoint::new(4, 1);
+        assert_eq!(8, a.manhattan(&b));
+        assert_eq!(6, a.chebyshev(&b));
+    }
+
+    #[test]
+    fn should_wrap() {
+        assert_eq!(IPoint::new(3, 0), IPoint::new(-2, 10).rem_euclid(5, 2));
+        assert_eq!(
+            Point::new(1usize, 2),
+            Point::new(6usize, 2).rem_euclid(5, 5)
+        );
+    }
+
+    #[test]
+    fn should_get_neighbors() {
+        let origin = Point::new(0usize, 0);
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(0, 1)],
+            origin.neighbors().collect_vec()
+        );
+        assert_eq!(3, origin.neighbors_diagonal().count());
+        assert_eq!(8, IPoint::new(0, 0).neighbors_diagonal().count());
+        assert_eq!(None, origin.step(Direction::Left));
+        assert_eq!(Some(Point::new(0, 1)), origin.step(Direction::Down));
+    }
+
+    #[test]
+    fn should_do_3d_arithmetic() {
+        let p = IPoint3::new(1, -2, 3);
+        assert_eq!(IPoint3::new(2, 0, 6), p + (1, 2, 3));
+        assert_eq!(IPoint3::new(-1, 2, -3), -p);
+        assert_eq!(IPoint3::new(2, -4, 6), 2 * p);
+        assert_eq!(6, p.manhattan(&IPoint3::new(0, 0, 0)));
+        assert_eq!(3, p.chebyshev(&IPoint3::new(0, 0, 0)));
+        assert_eq!(
+            None,
+            Point3::new(0usize, 1, 1).checked_sub(&Point3::new(1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn should_get_3d_neighbors() {
+        let p = IPoint3::new(5, 5, 5);
+        assert_eq!(6, p.neighbors().count());
+        assert_eq!(26, p.neighbors_diagonal().unique().count());
+        assert!(p.neighbors().all(|n| n.manhattan(&p) == 1));
+        assert_eq!(3, Point3::new(0usize, 0, 0).neighbors().count());
+        assert_eq!(7, Point3::new(0usize, 0, 0).neighbors_diagonal().count());
+    }
+
+    proptest! {
+        #[test]
+        fn should_undo_addition(p in ipoint(), q in ipoint()) {
+            prop_assert_eq!(p, (p + q) - q);
+            prop_assert_eq!(p, (p - q) + q);
+            prop_assert_eq!(p + q, q + p);
+            let mut r = p;
+            r += q;
+            r -= (q.x, q.y);
+            prop_assert_eq!(p, r);
+        }
+
+        #[test]
+        fn should_undo_addition_in_3d(p in ipoint3(), q in ipoint3()) {
+            prop_assert_eq!(p, (p + q) - q);
+            prop_assert_eq!(p, (p - q) + q);
+        }
+
+        #[test]
+        fn should_undo_checked_addition(ax: usize, ay: usize, bx: usize, by: usize) {
+            let (a, b) = (Point::new(ax, ay), Point::new(bx, by));
+            match a.checked_add(&b) {
+                Some(sum) => prop_assert_eq!(Some(a), sum.checked_sub(&b)),
+                None => prop_assert_eq!(a, a.wrapping_add(&b).wrapping_sub(&b)),
+            }
+        }
+
+        #[test]
+        fn should_measure_distance_between(p in ipoint(), q in ipoint()) {
+            prop_assert_eq!(p.manhattan(&q), q.manhattan(&p));
+            prop_assert_eq!(p.manhattan(&q), (p - q).manhattan(&IPoint::new(0, 0)));
+            prop_assert!(p.chebyshev(&q) <= p.manhattan(&q));
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Puzzle inputs aren't supposed to be shared, so they live in the repo
+// encrypted. The format is dead simple: a random 96-bit nonce followed by the
+// ChaCha20-Poly1305 ciphertext of the whole file.
+use anyhow::{anyhow, Result};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use itertools::Itertools;
+use std::{env, fs, path::PathBuf};
+
+pub const KEY_ENV: &str = "AOC_INPUT_KEY";
+pub const KEYFILE_ENV: &str = "AOC_INPUT_KEYFILE";
+pub const DEFAULT_KEYFILE: &str = ".input_key";
+pub const EXTENSION: &str = "enc";
+
+const NONCE_LEN: usize = 12;
+
+/// Where the key will be read from if it isn't set directly in the environment.
+pub fn keyfile_path() -> PathBuf {
+    PathBuf::from(env::var(KEYFILE_ENV).unwrap_or_else(|_| DEFAULT_KEYFILE.to_string()))
+}
+
+/// Loads the hex-encoded key from `$AOC_INPUT_KEY`, falling back to the keyfile.
+pub fn load_key() -> Result<Key> {
+    let hex = match env::var(KEY_ENV) {
+        Ok(k) => k,
+        Err(_) => {
+            let path = keyfile_path();
+            fs::read_to_string(&path).map_err(|e| {
+                anyhow!(
+                    "No input key: set {KEY_ENV} or create {} ({e})",
+                    path.display()
+                )
+            })?
+        }
+    };
+    let bytes = from_hex(hex.trim())?;
+    if bytes.len() != 32 {
+        return Err(anyhow!("Input key must be 32 bytes, got {}", bytes.len()));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+/// Generates a fresh random key, hex-encoded.
+pub fn generate_key() -> String {
+    to_hex(&ChaCha20Poly1305::generate_key(&mut OsRng))
+}
+
+pub fn encrypt(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow!("Encryption failed"))?;
+    Ok(nonce.iter().chain(ciphertext.iter()).copied().collect())
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < NONCE_LEN {
+        return Err(anyhow!("Encrypted input is truncated"));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(key);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Could not decrypt input (wrong key?)"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).join("")
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    if !s.is_ascii() || !s.len().is_multiple_of(2) {
+        return Err(anyhow!("Invalid hex string"));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| Ok(u8::from_str_radix(&s[i..i + 2], 16)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip() -> Result<()> {
+        let key = *Key::from_slice(&[7; 32]);
+        let encrypted = encrypt(&key, b"Game 1: 3 blue, 4 red")?;
+        assert_ne!(b"Game 1: 3 blue, 4 red".to_vec(), encrypted[NONCE_LEN..]);
+        assert_eq!(
+            b"Game 1: 3 blue, 4 red".to_vec(),
+            decrypt(&key, &encrypted)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_wrong_key() -> Result<()> {
+        let encrypted = encrypt(Key::from_slice(&[1; 32]), b"secret")?;
+        assert!(decrypt(Key::from_slice(&[2; 32]), &encrypted).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_round_trip_hex() -> Result<()> {
+        let bytes = vec![0, 1, 0xab, 0xff];
+        assert_eq!("0001abff", to_hex(&bytes));
+        assert_eq!(bytes, from_hex("0001abff")?);
+        assert!(from_hex("abc").is_err());
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
 // template for future days
-use crate::solver::Solver;
+use crate::{answer::Answer, solver::Solver};
 use anyhow::Result;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = Answer;
+    type PartTwo = Answer;
+
     const DAY: u8 = 0;
     const TITLE: &'static str = "UNKNOWN";
 
@@ -11,12 +14,12 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        Ok(0)
+    fn part_one(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unimplemented)
     }
 
-    fn part_two(&self) -> Result<usize> {
-        Ok(0)
+    fn part_two(&self, _input: &str) -> Result<Answer> {
+        Ok(Answer::Unimplemented)
     }
 }
 
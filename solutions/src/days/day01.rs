@@ -1,12 +1,12 @@
-use crate::solver::Solver;
-use anyhow::{anyhow, Ok, Result};
-use itertools::Itertools;
+use crate::{common::scanner::Scanner, solver::Solver};
+use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
-use pcre2::bytes::Regex;
-use phf_macros::phf_map;
 
 pub struct Solution;
-impl Solver<u32, u32> for Solution {
+impl Solver for Solution {
+    type PartOne = u32;
+    type PartTwo = u32;
+
     const DAY: u8 = 1;
     const TITLE: &'static str = "Trebuchet?!";
 
@@ -14,80 +14,52 @@ impl Solver<u32, u32> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<u32> {
-        let input = self.input().get()?;
-        get_total(&input, false)
+    fn part_one(&self, input: &str) -> Result<u32> {
+        get_total(input, &DIGITS)
     }
 
-    fn part_two(&self) -> Result<u32> {
-        let input = self.input().get()?;
-        get_total(&input, true)
+    fn part_two(&self, input: &str) -> Result<u32> {
+        get_total(input, &SPELLED_DIGITS)
     }
-}
-
-fn get_calibration_value(line: &str) -> Result<u32> {
-    let chars = line.chars().collect_vec();
-    let first = line.find(|c: char| c.is_digit(10));
-    let last = line.rfind(|c: char| c.is_digit(10));
 
-    if first == None || last == None {
-        return Err(anyhow!("Could not find any digits!"));
+    fn parse_input(&self, input: &str) -> Result<()> {
+        // Every digit is also a spelled digit, so this takes input meant for
+        // either part (not every line of part two's has a plain digit in it)
+        get_total(input, &SPELLED_DIGITS)?;
+        Ok(())
     }
+}
 
-    let calibration = chars[first.unwrap()].to_digit(10).unwrap() * 10
-        + chars[last.unwrap()].to_digit(10).unwrap();
+const ENGLISH: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
 
-    Ok(calibration)
+lazy_static! {
+    static ref DIGITS: Scanner<u32> = digit_scanner(&[]).unwrap();
+    static ref SPELLED_DIGITS: Scanner<u32> = digit_scanner(&ENGLISH).unwrap();
 }
 
-static DIGITS: phf::Map<&'static str, u32> = phf_map! {
-    "zero" => 0,
-    "one" => 1,
-    "two" => 2,
-    "three" => 3,
-    "four" => 4,
-    "five" => 5,
-    "six" => 6,
-    "seven" => 7,
-    "eight" => 8,
-    "nine" => 9,
-    "0" => 0,
-    "1" => 1,
-    "2" => 2,
-    "3" => 3,
-    "4" => 4,
-    "5" => 5,
-    "6" => 6,
-    "7" => 7,
-    "8" => 8,
-    "9" => 9,
-};
-lazy_static! {
-    static ref RE: Regex =
-        Regex::new(r"(?=(\d|zero|one|two|three|four|five|six|seven|eight|nine))").unwrap();
+/// Finds the digits, and `words[n]` as another way of writing n. Words can
+/// overlap ("eightwo" has both an 8 and a 2 in it).
+fn digit_scanner(words: &[&str]) -> Result<Scanner<u32>> {
+    let digits = (0..10).map(|d| (d.to_string(), d));
+    let words = words.iter().zip(0..).map(|(w, d)| (w.to_string(), d));
+    Scanner::new(digits.chain(words))
 }
-fn get_stringy_calibration(line: &str) -> Result<u32> {
-    let digits = RE
-        .captures_iter(line.as_bytes())
-        .map(|c| {
-            let m = c.unwrap();
-            let v = std::str::from_utf8(&m[1]).unwrap();
-            DIGITS[&v]
-        })
-        .collect_vec();
-    let val = digits[0] * 10 + *digits.last().unwrap();
-    Ok(val)
+
+fn get_calibration_value(line: &str, digits: &Scanner<u32>) -> Result<u32> {
+    match (digits.first(line), digits.last(line)) {
+        (Some(first), Some(last)) => Ok(first.value * 10 + last.value),
+        _ => Err(anyhow!("Could not find any digits in {line:?}")),
+    }
 }
 
-fn get_total(input: &str, stringy: bool) -> Result<u32> {
+fn get_total(input: &str, digits: &Scanner<u32>) -> Result<u32> {
     // Yeah, this could be a one-liner, but then we wouldn't propagate the
     // Result::Err easily, now would we?
     let mut sum: u32 = 0;
     for line in input.lines() {
-        sum += match stringy {
-            true => get_stringy_calibration(&line)?,
-            false => get_calibration_value(&line)?,
-        }
+        sum += get_calibration_value(line, digits)?;
     }
     Ok(sum)
 }
@@ -111,7 +83,7 @@ zoneight234
     #[test]
     fn should_get_calibration_value() -> Result<()> {
         let line = "1abc2";
-        let calibration = get_calibration_value(&line)?;
+        let calibration = get_calibration_value(line, &DIGITS)?;
         assert_eq!(12, calibration);
         Ok(())
     }
@@ -119,30 +91,54 @@ zoneight234
     #[test]
     fn should_get_single_digit() -> Result<()> {
         let line = "ab1cd";
-        let cal = get_calibration_value(&line)?;
+        let cal = get_calibration_value(line, &DIGITS)?;
         assert_eq!(11, cal);
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let res = get_total(EXAMPLE_INPUT_PART_ONE, false)?;
-        assert_eq!(142, res);
+        assert_eq!(142, Solution::new().part_one(EXAMPLE_INPUT_PART_ONE)?);
         Ok(())
     }
 
     #[test]
     fn should_get_strings_too() -> Result<()> {
         let test = "eightjzqzhrllg1oneightfck";
-        let val = get_stringy_calibration(&test)?;
+        let val = get_calibration_value(test, &SPELLED_DIGITS)?;
         assert_eq!(88, val);
         Ok(())
     }
 
+    #[test]
+    fn should_fail_without_digits() {
+        assert!(get_calibration_value("abc", &DIGITS).is_err());
+        assert!(get_calibration_value("one", &DIGITS).is_err());
+        assert!(get_calibration_value("abc", &SPELLED_DIGITS).is_err());
+        assert!(Solution::new().part_two("two1nine\nnope").is_err());
+        assert!(Solution::new().parse_input("two1nine\nnope").is_err());
+    }
+
+    #[test]
+    fn should_parse_either_example() -> Result<()> {
+        Solution::new().parse_input(EXAMPLE_INPUT_PART_ONE)?;
+        Solution::new().parse_input(EXAMPLE_INPUT_PART_TWO)?;
+        Ok(())
+    }
+
+    #[test]
+    fn should_use_other_words() -> Result<()> {
+        let german = digit_scanner(&[
+            "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ])?;
+        assert_eq!(53, get_calibration_value("xfünfzweinsdreiy", &german)?);
+        assert_eq!(71, get_calibration_value("siebeneins", &german)?);
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let res = get_total(EXAMPLE_INPUT_PART_TWO, true)?;
-        assert_eq!(281, res);
+        assert_eq!(281, Solution::new().part_two(EXAMPLE_INPUT_PART_TWO)?);
         Ok(())
     }
 }
@@ -1,11 +1,13 @@
-use crate::solver::Solver;
+use crate::{input::lines_as, solver::Solver};
 use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::{collections::HashMap, str::FromStr};
+use itertools::{Either, Itertools};
+use std::{collections::BTreeMap, fmt, iter, str::FromStr};
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 2;
     const TITLE: &'static str = "Cube Conundrum";
 
@@ -13,84 +15,141 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let constraints = vec![(Cube::Red, 12), (Cube::Green, 13), (Cube::Blue, 14)];
-        let games = self.input().get_lines_as::<Game>()?;
-        Ok(sum_possible(&games, &constraints))
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let games = lines_as::<Game>(input)?;
+        Ok(sum_possible(&games, &Cubes::elf_bag()))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let games = self.input().get_lines_as::<Game>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let games = lines_as::<Game>(input)?;
         Ok(sum_power(&games))
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        lines_as::<Game>(input)?;
+        Ok(())
+    }
+}
+
+/// How many cubes there are of each colour, written like "3 blue, 4 red".
+/// Any colour name will do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cubes(BTreeMap<String, usize>);
+impl FromStr for Cubes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut cubes = Cubes::default();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (count, color) = part
+                .split_once(' ')
+                .ok_or(anyhow!("Invalid cubes: {part}"))?;
+            let color = color.trim();
+            if color.is_empty() {
+                return Err(anyhow!("No colour given: {part}"));
+            }
+            // Mentioning a colour twice shows both lots
+            let total = cubes.0.entry(color.to_string()).or_insert(0);
+            *total = total
+                .checked_add(count.parse()?)
+                .ok_or(anyhow!("Too many cubes: {s}"))?;
+        }
+        Ok(cubes)
+    }
 }
+impl fmt::Display for Cubes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.is_empty() {
+            true => write!(f, "no cubes"),
+            false => write!(
+                f,
+                "{}",
+                self.0.iter().map(|(c, n)| format!("{n} {c}")).join(", ")
+            ),
+        }
+    }
+}
+impl Cubes {
+    /// What the Elf has in the bag for part one.
+    fn elf_bag() -> Self {
+        Cubes(BTreeMap::from([
+            ("red".to_string(), 12),
+            ("green".to_string(), 13),
+            ("blue".to_string(), 14),
+        ]))
+    }
 
-lazy_static! {
-    static ref GAME_ID: Regex = Regex::new(r"Game (\d+): ").unwrap();
-    static ref REVEAL_CUBES: Regex = Regex::new(r"(\d+) (red|green|blue)").unwrap();
+    /// Colours that aren't mentioned have none.
+    fn get(&self, color: &str) -> usize {
+        self.0.get(color).copied().unwrap_or(0)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Cube {
-    Red,
-    Green,
-    Blue,
+/// The first time a game showed more cubes than the bag could have held.
+#[derive(Debug, PartialEq, Eq)]
+struct Violation<'a> {
+    reveal: usize,
+    color: &'a str,
+    shown: usize,
+    in_bag: usize,
 }
 
 struct Game {
     id: usize,
-    required: HashMap<Cube, usize>,
+    reveals: Vec<Cubes>,
 }
 impl FromStr for Game {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let gid_cap = GAME_ID.captures(s).ok_or(anyhow!("Invalid game"))?;
-        let id = gid_cap[1].parse::<usize>()?;
-        let mut required = HashMap::new();
-        for cubes in REVEAL_CUBES.captures_iter(s) {
-            let color = match &cubes[2] {
-                "red" => Cube::Red,
-                "green" => Cube::Green,
-                "blue" => Cube::Blue,
-                _ => {
-                    return Err(anyhow!("Invalid color"));
-                }
-            };
-            let num = cubes[1].parse::<usize>()?;
-            let cur = required.entry(color).or_insert(0);
-            if &num > cur {
-                *cur = num;
-            }
-        }
-        Ok(Game { id, required })
+        let (name, reveals) = s.split_once(':').ok_or(anyhow!("Invalid game: {s}"))?;
+        let id = name
+            .strip_prefix("Game ")
+            .ok_or(anyhow!("Invalid game: {s}"))?
+            .parse()?;
+        let reveals = reveals.split(';').map(str::parse).try_collect()?;
+        Ok(Game { id, reveals })
     }
 }
 impl Game {
-    fn is_possible(&self, constraints: &[(Cube, usize)]) -> bool {
-        for con in constraints {
-            if self.required.get(&con.0).or(Some(&0)).unwrap() > &con.1 {
-                return false;
-            }
+    /// The fewest cubes of each colour that could have been in the bag.
+    fn minimal_bag(&self) -> Cubes {
+        let mut bag = Cubes::default();
+        for (color, &n) in self.reveals.iter().flat_map(|r| &r.0) {
+            let most = bag.0.entry(color.clone()).or_insert(0);
+            *most = n.max(*most);
         }
-        true
+        bag
+    }
+
+    fn first_violation<'a>(&'a self, bag: &Cubes) -> Option<Violation<'a>> {
+        self.reveals.iter().enumerate().find_map(|(i, reveal)| {
+            reveal
+                .0
+                .iter()
+                .find(|(c, &n)| n > bag.get(c))
+                .map(|(color, &shown)| Violation {
+                    reveal: i + 1,
+                    color,
+                    shown,
+                    in_bag: bag.get(color),
+                })
+        })
+    }
+
+    fn is_possible(&self, bag: &Cubes) -> bool {
+        self.first_violation(bag).is_none()
     }
 
     fn power(&self) -> usize {
-        self.required.values().product()
+        self.minimal_bag().0.values().product()
     }
 }
 
-fn sum_possible(games: &[Game], constraints: &[(Cube, usize)]) -> usize {
+fn sum_possible(games: &[Game], bag: &Cubes) -> usize {
     games
         .iter()
-        .filter_map(|g| {
-            if g.is_possible(&constraints) {
-                Some(g.id)
-            } else {
-                None
-            }
-        })
+        .filter_map(|g| if g.is_possible(bag) { Some(g.id) } else { None })
         .sum()
 }
 
@@ -98,11 +157,126 @@ fn sum_power(games: &[Game]) -> usize {
     games.iter().map(|g| g.power()).sum()
 }
 
+/// The fewest cubes to add to `bag` so that at least `wanted` of the games
+/// would have been possible, or None if there aren't that many games.
+///
+/// Each colour ends up with either what's already there or exactly what one
+/// of the games needs, so this tries every combination of those for all but
+/// one colour, and picks the cheapest amount of the last colour that lets
+/// enough games through. That's exponential in the number of colours, so the
+/// combinations are only made one at a time, and any that already cost more
+/// than the best so far are dropped without checking the games.
+fn smallest_change(games: &[Game], bag: &Cubes, wanted: usize) -> Option<Cubes> {
+    if wanted > games.len() {
+        return None;
+    }
+    if wanted == 0 {
+        return Some(Cubes::default());
+    }
+    let needs = games.iter().map(|g| g.minimal_bag()).collect_vec();
+    let colors = needs
+        .iter()
+        .flat_map(|n| n.0.keys())
+        .unique()
+        .sorted()
+        .collect_vec();
+    let Some((last, rest)) = colors.split_last() else {
+        // No cubes were ever shown, so anything goes
+        return Some(Cubes::default());
+    };
+    let amounts = |color: &str| {
+        needs
+            .iter()
+            .map(|n| n.get(color).max(bag.get(color)))
+            .unique()
+            .collect_vec()
+    };
+    // With no other colours there's still the one (empty) combination
+    let combinations = match rest.is_empty() {
+        true => Either::Left(iter::once(vec![])),
+        false => Either::Right(rest.iter().map(|c| amounts(c)).multi_cartesian_product()),
+    };
+
+    let mut best: Option<(usize, Cubes)> = None;
+    for combo in combinations {
+        let partial = Cubes(rest.iter().map(|c| c.to_string()).zip(combo).collect());
+        let partial_cost: usize = partial.0.iter().map(|(c, n)| n - bag.get(c)).sum();
+        if best.as_ref().is_some_and(|(c, _)| partial_cost >= *c) {
+            continue;
+        }
+        // Of the games these amounts allow, the cheapest way to let through
+        // enough of them is the `wanted`th smallest need for the last colour
+        let mut last_needs = needs
+            .iter()
+            .filter(|n| rest.iter().all(|c| partial.get(c) >= n.get(c)))
+            .map(|n| n.get(last).max(bag.get(last)))
+            .collect_vec();
+        if last_needs.len() < wanted {
+            continue;
+        }
+        last_needs.sort();
+        let last_amount = last_needs[wanted - 1];
+        let mut added = Cubes::default();
+        for (color, n) in partial
+            .0
+            .into_iter()
+            .chain([(last.to_string(), last_amount)])
+        {
+            let extra = n - bag.get(&color);
+            if extra > 0 {
+                added.0.insert(color, extra);
+            }
+        }
+        let cost = added.0.values().sum();
+        if best.as_ref().is_none_or(|(c, _)| cost < *c) {
+            best = Some((cost, added));
+        }
+    }
+    best.map(|(_, added)| added)
+}
+
+/// Answers `day02 query`: which games `bag` allows and why the rest were
+/// impossible, what each needed at least, and (if `wanted` is given) what
+/// to add to the bag so that many would have been possible.
+pub fn query(input: &str, bag: &Cubes, wanted: Option<usize>) -> Result<String> {
+    let games = lines_as::<Game>(input)?;
+    let mut lines = vec![];
+    for game in &games {
+        let verdict = match game.first_violation(bag) {
+            None => "possible".to_string(),
+            Some(v) => format!(
+                "impossible, reveal {} showed {} {} but the bag has {}",
+                v.reveal, v.shown, v.color, v.in_bag
+            ),
+        };
+        lines.push(format!(
+            "Game {}: {verdict}; needs at least {}",
+            game.id,
+            game.minimal_bag()
+        ));
+    }
+    let possible = games.iter().filter(|g| g.is_possible(bag)).count();
+    lines.push(format!(
+        "{possible} of {} games possible with {bag}",
+        games.len()
+    ));
+    if let Some(wanted) = wanted {
+        lines.push(match smallest_change(&games, bag, wanted) {
+            None => format!("There are only {} games", games.len()),
+            Some(added) if added == Cubes::default() => {
+                format!("{wanted} games are already possible")
+            }
+            Some(added) => format!("Add {added} to make {wanted} games possible"),
+        });
+    }
+    Ok(lines.join("\n"))
+}
+
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
-
     use super::*;
+    use crate::common::differential;
+    use proptest::{collection::vec, prelude::*};
 
     const EXAMPLE_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
@@ -114,9 +288,28 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     fn should_parse() -> Result<()> {
         let test = "Game 1: 1 red, 2 green; 3 blue; 4 red";
         let game = test.parse::<Game>()?;
-        assert_eq!(Some(&4), game.required.get(&Cube::Red));
-        assert_eq!(Some(&2), game.required.get(&Cube::Green));
-        assert_eq!(Some(&3), game.required.get(&Cube::Blue));
+        assert_eq!(1, game.id);
+        assert_eq!(3, game.reveals.len());
+        assert_eq!("1 red, 2 green".parse::<Cubes>()?, game.reveals[0]);
+        let bag = game.minimal_bag();
+        assert_eq!(4, bag.get("red"));
+        assert_eq!(2, bag.get("green"));
+        assert_eq!(3, bag.get("blue"));
+        assert!("Game 1: 1 red, green".parse::<Game>().is_err());
+        assert!("Game x: 1 red".parse::<Game>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_any_color() -> Result<()> {
+        let cubes = "2 mauve, 1 burnt sienna, 3 mauve".parse::<Cubes>()?;
+        assert_eq!(5, cubes.get("mauve"));
+        assert_eq!(1, cubes.get("burnt sienna"));
+        assert_eq!(0, cubes.get("red"));
+        assert_eq!("1 burnt sienna, 5 mauve", cubes.to_string());
+        assert!(format!("{} red, 1 red", usize::MAX)
+            .parse::<Cubes>()
+            .is_err());
         Ok(())
     }
 
@@ -124,8 +317,7 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     fn should_be_possible() -> Result<()> {
         let test = "Game 1: 1 red, 2 green; 3 blue; 4 red";
         let game = test.parse::<Game>()?;
-        let constraints = vec![(Cube::Red, 5)];
-        assert_eq!(true, game.is_possible(&constraints));
+        assert_eq!(true, game.is_possible(&"5 red, 2 green, 3 blue".parse()?));
         Ok(())
     }
 
@@ -133,20 +325,21 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     fn should_be_impossible() -> Result<()> {
         let test = "Game 1: 1 red, 2 green; 3 blue; 4 red";
         let game = test.parse::<Game>()?;
-        let constraints = vec![(Cube::Red, 1)];
-        assert_eq!(false, game.is_possible(&constraints));
+        let bag = "1 red, 5 green, 5 blue".parse()?;
+        assert_eq!(false, game.is_possible(&bag));
+        let expected = Violation {
+            reveal: 3,
+            color: "red",
+            shown: 4,
+            in_bag: 1,
+        };
+        assert_eq!(Some(expected), game.first_violation(&bag));
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let constraints = vec![(Cube::Red, 12), (Cube::Green, 13), (Cube::Blue, 14)];
-        let games = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Game>().ok())
-            .collect_vec();
-        let sum = sum_possible(&games, &constraints);
-        assert_eq!(8, sum);
+        assert_eq!(8, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -160,11 +353,111 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let games = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Game>().ok())
-            .collect_vec();
-        assert_eq!(2286, sum_power(&games));
+        assert_eq!(2286, Solution::new().part_two(EXAMPLE_INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_smallest_change() -> Result<()> {
+        let games = lines_as::<Game>(EXAMPLE_INPUT)?;
+        let bag = Cubes::elf_bag();
+        assert_eq!(Some(Cubes::default()), smallest_change(&games, &bag, 3));
+        // Game 4 only needs one more blue and two more red, where game 3
+        // would need eight more red
+        assert_eq!(
+            Some("2 red, 1 blue".parse()?),
+            smallest_change(&games, &bag, 4)
+        );
+        assert_eq!(
+            Some("8 red, 1 blue".parse()?),
+            smallest_change(&games, &bag, 5)
+        );
+        assert_eq!(None, smallest_change(&games, &bag, 6));
+        // Game 2 is the cheapest to play with an empty bag
+        let empty = Cubes::default();
+        assert_eq!(
+            Some("1 red, 3 green, 4 blue".parse()?),
+            smallest_change(&games, &empty, 1)
+        );
+        // Only one colour, so nothing to combine
+        let mauve = lines_as::<Game>("Game 1: 3 mauve\nGame 2: 5 mauve; 1 mauve")?;
+        assert_eq!(Some("5 mauve".parse()?), smallest_change(&mauve, &empty, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn should_answer_queries() -> Result<()> {
+        let answer = query(EXAMPLE_INPUT, &Cubes::elf_bag(), Some(4))?;
+        let lines = answer.lines().collect_vec();
+        assert_eq!(7, lines.len());
+        assert_eq!(
+            "Game 1: possible; needs at least 6 blue, 2 green, 4 red",
+            lines[0]
+        );
+        assert_eq!(
+            "Game 3: impossible, reveal 1 showed 20 red but the bag has 12; needs at least 6 blue, 13 green, 20 red",
+            lines[2]
+        );
+        assert_eq!(
+            "3 of 5 games possible with 14 blue, 13 green, 12 red",
+            lines[5]
+        );
+        assert_eq!("Add 1 blue, 2 red to make 4 games possible", lines[6]);
         Ok(())
     }
+
+    /// What `smallest_change` should cost, by trying every set of games.
+    fn cheapest_by_brute_force(games: &[Game], bag: &Cubes, wanted: usize) -> Option<usize> {
+        (0..games.len())
+            .combinations(wanted)
+            .map(|chosen| {
+                let mut needed = Cubes::default();
+                for &i in &chosen {
+                    for (color, n) in games[i].minimal_bag().0 {
+                        let most = needed.0.entry(color).or_insert(0);
+                        *most = n.max(*most);
+                    }
+                }
+                needed
+                    .0
+                    .iter()
+                    .map(|(c, &n)| n.saturating_sub(bag.get(c)))
+                    .sum()
+            })
+            .min()
+    }
+
+    #[test]
+    fn should_find_smallest_change_differentially() {
+        let cubes = || vec(("[0-6]", "red|green|blue|teal"), 0..4);
+        let show =
+            |cubes: Vec<(String, String)>| cubes.iter().map(|(n, c)| format!("{n} {c}")).join(", ");
+        let games = vec(vec(cubes(), 1..4), 1..7).prop_map(move |games| {
+            games
+                .into_iter()
+                .enumerate()
+                .map(|(i, reveals)| {
+                    let reveals = reveals.into_iter().map(show).join("; ");
+                    format!("Game {}: {reveals}", i + 1)
+                })
+                .join("\n")
+        });
+        let case = (games, (cubes().prop_map(show), 0..8usize));
+        differential::check("day02", case, |(input, (bag, wanted))| {
+            let games = lines_as::<Game>(input).unwrap();
+            let bag = bag.parse::<Cubes>().unwrap();
+            let change = smallest_change(&games, &bag, *wanted);
+            let cost = change.as_ref().map(|c| c.0.values().sum::<usize>());
+            prop_assert_eq!(cheapest_by_brute_force(&games, &bag, *wanted), cost);
+            if let Some(change) = change {
+                let mut bigger = bag.clone();
+                for (color, n) in change.0 {
+                    *bigger.0.entry(color).or_insert(0) += n;
+                }
+                let possible = games.iter().filter(|g| g.is_possible(&bigger)).count();
+                prop_assert!(possible >= *wanted);
+            }
+            Ok(())
+        });
+    }
 }
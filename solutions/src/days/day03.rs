@@ -3,12 +3,15 @@ use std::{
     str::FromStr,
 };
 
-use crate::solver::Solver;
+use crate::{common::IPoint, solver::Solver};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 3;
     const TITLE: &'static str = "Gear Ratios";
 
@@ -16,50 +19,44 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let schematic = self.input().get_as::<Schematic>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let schematic = input.parse::<Schematic>()?;
         let parts = schematic.get_part_numbers();
         Ok(parts.iter().sum())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let schematic = self.input().get_as::<Schematic>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let schematic = input.parse::<Schematic>()?;
         let ratios = schematic.get_gear_ratios();
         Ok(ratios.iter().sum())
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-impl Point {
-    fn new(x: isize, y: isize) -> Self {
-        Point { x, y }
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Schematic>()?;
+        Ok(())
     }
 }
 
 struct Schematic {
-    numbers: Vec<(Point, Point, usize)>,
-    symbols: HashSet<Point>,
-    gears: HashSet<Point>,
+    numbers: Vec<(IPoint, IPoint, usize)>,
+    symbols: HashSet<IPoint>,
+    gears: HashSet<IPoint>,
 }
 impl FromStr for Schematic {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let mut gears: HashSet<Point> = HashSet::new();
-        let mut symbols: HashSet<Point> = HashSet::new();
-        let mut numbers: Vec<(Point, Point, usize)> = vec![];
+        let mut gears: HashSet<IPoint> = HashSet::new();
+        let mut symbols: HashSet<IPoint> = HashSet::new();
+        let mut numbers: Vec<(IPoint, IPoint, usize)> = vec![];
         let mut cur_num: Vec<usize> = vec![];
-        let mut cur_start: Option<Point> = None;
-        let mut cur_stop: Option<Point> = None;
+        let mut cur_start: Option<IPoint> = None;
+        let mut cur_stop: Option<IPoint> = None;
 
         for (_y, row) in s.lines().enumerate() {
             let y = isize::try_from(_y)?;
             if matches!((cur_start, cur_stop), (Some(_), Some(_))) {
-                let num = digits_to_number(&cur_num);
+                let num = digits_to_number(&cur_num)?;
                 numbers.push((cur_start.unwrap(), cur_stop.unwrap(), num));
                 cur_num.clear();
                 cur_start = None;
@@ -71,14 +68,14 @@ impl FromStr for Schematic {
                     '0'..='9' => {
                         cur_num.push(col.to_digit(10).unwrap().try_into()?);
                         if cur_start.is_none() {
-                            cur_start = Some(Point { x, y });
+                            cur_start = Some(IPoint { x, y });
                         }
-                        cur_stop = Some(Point { x, y });
+                        cur_stop = Some(IPoint { x, y });
                     }
                     _ => {
                         match (cur_start, cur_stop) {
                             (Some(start), Some(stop)) => {
-                                let num = digits_to_number(&cur_num);
+                                let num = digits_to_number(&cur_num)?;
                                 numbers.push((start, stop, num));
                                 cur_num.clear();
                                 cur_start = None;
@@ -90,11 +87,11 @@ impl FromStr for Schematic {
                         match col {
                             '.' => continue,
                             '*' => {
-                                gears.insert(Point { x, y });
+                                gears.insert(IPoint { x, y });
                             }
                             _ => (),
                         }
-                        symbols.insert(Point { x, y });
+                        symbols.insert(IPoint { x, y });
                     }
                 }
             }
@@ -121,7 +118,7 @@ impl Schematic {
     }
 
     fn get_gear_ratios(&self) -> Vec<usize> {
-        let mut geared: HashMap<Point, (usize, usize)> = HashMap::new();
+        let mut geared: HashMap<IPoint, (usize, usize)> = HashMap::new();
         for &(start, stop, n) in self.numbers.iter() {
             for &a in get_adjacencies(start, stop).iter() {
                 if self.gears.contains(&a) {
@@ -138,23 +135,22 @@ impl Schematic {
     }
 }
 
-fn digits_to_number(digits: &[usize]) -> usize {
-    let mut num = 0;
-    for i in 0..digits.len() {
-        num += digits[i] * 10usize.pow((digits.len() - 1 - i).try_into().unwrap());
-    }
-    num
+fn digits_to_number(digits: &[usize]) -> Result<usize> {
+    digits
+        .iter()
+        .try_fold(0usize, |num, &d| num.checked_mul(10)?.checked_add(d))
+        .ok_or(anyhow!("Number too big: {digits:?}"))
 }
 
-fn get_adjacencies(start: Point, stop: Point) -> Vec<Point> {
+fn get_adjacencies(start: IPoint, stop: IPoint) -> Vec<IPoint> {
     let mut ret = vec![];
     // Since all numbers are one row, we only need to loop for top & bottom
     for x in start.x - 1..=stop.x + 1 {
-        ret.push(Point::new(x, start.y - 1));
-        ret.push(Point::new(x, start.y + 1));
+        ret.push(IPoint::new(x, start.y - 1));
+        ret.push(IPoint::new(x, start.y + 1));
     }
-    ret.push(Point::new(start.x - 1, start.y));
-    ret.push(Point::new(stop.x + 1, stop.y));
+    ret.push(IPoint::new(start.x - 1, start.y));
+    ret.push(IPoint::new(stop.x + 1, stop.y));
     ret
 }
 
@@ -176,9 +172,9 @@ mod tests {
     #[test]
     fn should_parse() -> Result<()> {
         let test = "..123..*..";
-        let expected_num = vec![(Point::new(2, 0), Point::new(4, 0), 123)];
+        let expected_num = vec![(IPoint::new(2, 0), IPoint::new(4, 0), 123)];
         let mut expected_sym = HashSet::new();
-        expected_sym.insert(Point::new(7, 0));
+        expected_sym.insert(IPoint::new(7, 0));
         let schematic = test.parse::<Schematic>()?;
         assert_eq!(1, schematic.numbers.len());
         assert_eq!(1, schematic.symbols.len());
@@ -190,20 +186,20 @@ mod tests {
     #[test]
     fn should_get_adjacent() -> Result<()> {
         let expected = vec![
-            Point::new(0, 0),
-            Point::new(0, 2),
-            Point::new(1, 0),
-            Point::new(1, 2),
-            Point::new(2, 0),
-            Point::new(2, 2),
-            Point::new(3, 0),
-            Point::new(3, 2),
-            Point::new(4, 0),
-            Point::new(4, 2),
-            Point::new(0, 1),
-            Point::new(4, 1),
+            IPoint::new(0, 0),
+            IPoint::new(0, 2),
+            IPoint::new(1, 0),
+            IPoint::new(1, 2),
+            IPoint::new(2, 0),
+            IPoint::new(2, 2),
+            IPoint::new(3, 0),
+            IPoint::new(3, 2),
+            IPoint::new(4, 0),
+            IPoint::new(4, 2),
+            IPoint::new(0, 1),
+            IPoint::new(4, 1),
         ];
-        let actual = get_adjacencies(Point::new(1, 1), Point::new(3, 1));
+        let actual = get_adjacencies(IPoint::new(1, 1), IPoint::new(3, 1));
         assert_eq!(expected, actual);
         Ok(())
     }
@@ -219,9 +215,7 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let schematic = EXAMPLE_INPUT.parse::<Schematic>()?;
-        let parts = schematic.get_part_numbers();
-        assert_eq!(4361usize, parts.iter().sum());
+        assert_eq!(4361, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -235,9 +229,7 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let schematic = EXAMPLE_INPUT.parse::<Schematic>()?;
-        let ratios = schematic.get_gear_ratios();
-        assert_eq!(467835usize, ratios.iter().sum());
+        assert_eq!(467835, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
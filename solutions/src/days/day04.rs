@@ -3,11 +3,14 @@ use std::{
     str::FromStr,
 };
 
-use crate::solver::Solver;
+use crate::{input::lines_as, solver::Solver};
 use anyhow::{anyhow, Result};
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 4;
     const TITLE: &'static str = "Scratchcards";
 
@@ -15,15 +18,20 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let scratchers = self.input().get_lines_as::<Scratcher>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let scratchers = lines_as::<Scratcher>(input)?;
         Ok(scratchers.iter().map(|s| s.score()).sum())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let scratchers = self.input().get_lines_as::<Scratcher>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let scratchers = lines_as::<Scratcher>(input)?;
         Ok(play_game(&scratchers))
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        lines_as::<Scratcher>(input)?;
+        Ok(())
+    }
 }
 
 struct Scratcher {
@@ -36,12 +44,14 @@ impl FromStr for Scratcher {
     fn from_str(s: &str) -> Result<Self> {
         let mut numbers = HashSet::new();
         let mut winners = HashSet::new();
-        let win_start = s.find(':').ok_or(anyhow!("Invalid scratch card!"))?;
-        let num_start = s.find('|').ok_or(anyhow!("Invalid scratch card!"))?;
-        for winner in s[win_start + 1..num_start - 1].split_whitespace() {
+        let (_, card) = s.split_once(':').ok_or(anyhow!("Invalid scratch card!"))?;
+        let (winning, mine) = card
+            .split_once('|')
+            .ok_or(anyhow!("Invalid scratch card!"))?;
+        for winner in winning.split_whitespace() {
             winners.insert(winner.parse()?);
         }
-        for number in s[num_start + 1..].split_whitespace() {
+        for number in mine.split_whitespace() {
             numbers.insert(number.parse()?);
         }
         Ok(Scratcher { numbers, winners })
@@ -107,12 +117,7 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let scratchers = EXAMPLE_INPUT
-            .lines()
-            .map(|l| l.parse::<Scratcher>().unwrap());
-        let scores = scratchers.map(|s| s.score()).collect_vec();
-        let total: usize = scores.iter().sum();
-        assert_eq!(13, total);
+        assert_eq!(13, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -132,12 +137,7 @@ Game 3: 1 2 3 | 4 5 6";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let scratchers = EXAMPLE_INPUT
-            .lines()
-            .map(|l| l.parse::<Scratcher>().unwrap())
-            .collect_vec();
-        let total = play_game(&scratchers);
-        assert_eq!(30, total);
+        assert_eq!(30, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
@@ -1,10 +1,16 @@
-use crate::solver::Solver;
+use crate::{
+    common::interval::{Interval, IntervalSet},
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use std::str::FromStr;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 5;
     const TITLE: &'static str = "If You Give A Seed A Fertilizer";
 
@@ -12,16 +18,20 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let almanac = self.input().get_as::<Almanac>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let almanac = input.parse::<Almanac>()?;
         let mapped = almanac.map_seeds();
         Ok(*mapped.iter().min().unwrap())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let almanac = self.input().get_as::<Almanac>()?;
-        let mapped = almanac.find_min_faster();
-        Ok(mapped)
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let almanac = input.parse::<Almanac>()?;
+        almanac.find_min_faster()
+    }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Almanac>()?;
+        Ok(())
     }
 }
 
@@ -36,15 +46,20 @@ impl FromStr for Mapping {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let specs = s.split(' ').filter_map(|x| x.parse().ok()).collect_vec();
-        if specs.len() != 3 {
+        let specs = s
+            .split(' ')
+            .filter_map(|x| x.parse::<usize>().ok())
+            .collect_vec();
+        let [dest_min, source_min, len] = specs[..] else {
             return Err(anyhow!("Invalid range specification"));
-        }
-        let offset = specs[2] - 1;
-        let dest_min = specs[0];
-        let dest_max = dest_min + offset;
-        let source_min = specs[1];
-        let source_max = source_min + offset;
+        };
+        // The ranges are stored inclusively, which an empty one can't be
+        let offset = len
+            .checked_sub(1)
+            .ok_or(anyhow!("Empty range specification"))?;
+        let too_big = || anyhow!("Range specification too big");
+        let dest_max = dest_min.checked_add(offset).ok_or_else(too_big)?;
+        let source_max = source_min.checked_add(offset).ok_or_else(too_big)?;
 
         Ok(Mapping {
             source_min,
@@ -66,9 +81,11 @@ impl Mapping {
         }
     }
 
-    fn overlaps(&self, range: &(usize, usize)) -> bool {
-        range.0 <= self.source_min && range.1 >= self.source_min
-            || range.0 <= self.source_max && range.0 >= self.source_min
+    fn piece(&self) -> (Interval<usize>, usize) {
+        (
+            Interval::inclusive(self.source_min, self.source_max),
+            self.dest_min,
+        )
     }
 }
 
@@ -131,63 +148,36 @@ impl Almanac {
         ret
     }
 
-    fn find_min_faster(&self) -> usize {
-        let seed_ranges: Vec<(&usize, &usize)> = self.seeds.iter().tuples().collect_vec();
-        let mut final_ranges: Vec<(usize, usize)> = vec![];
-        for (&seed_min, &seed_count) in seed_ranges {
-            let mut ranges = vec![(seed_min, seed_min + seed_count)];
-            for group in &self.mappings {
-                let mut mapped = vec![];
-                let mut min_mapped = usize::MAX;
-                let mut max_mapped = 0;
-                for &(min, max) in &ranges {
-                    let mut was_mapped = false;
-                    for mapping in group {
-                        if mapping.overlaps(&(min, max)) {
-                            was_mapped = true;
-                            let start = if min < mapping.source_min {
-                                mapping.dest_min
-                            } else {
-                                mapping.dest_min + (min - mapping.source_min)
-                            };
-                            let end = if max > mapping.source_max {
-                                mapping.dest_max
-                            } else {
-                                mapping.dest_min + (max - mapping.source_min)
-                            };
-                            mapped.push((start, end));
-                            if min_mapped > mapping.source_min {
-                                min_mapped = mapping.source_min;
-                            }
-                            if max_mapped < mapping.source_max {
-                                max_mapped = mapping.source_max;
-                            }
-                        }
-                    }
-                    // remainders
-                    if min_mapped < usize::MAX && min < min_mapped {
-                        mapped.push((min, min_mapped - 1));
-                    }
-                    if max_mapped > 0 && max > max_mapped {
-                        mapped.push((max_mapped + 1, max));
-                    }
-                    if !was_mapped {
-                        mapped.push((min, max));
-                    }
-                }
-                ranges.clear();
-                ranges.append(&mut mapped);
-                mapped.clear();
-            }
-            final_ranges.append(&mut ranges);
-        }
-        final_ranges.iter().map(|range| range.0).min().unwrap()
+    fn find_min_faster(&self) -> Result<usize> {
+        let seeds: IntervalSet<usize> = self
+            .seeds
+            .iter()
+            .tuples()
+            .map(|(&start, &count)| Interval::new(start, start + count))
+            .collect();
+        let located = self.mappings.iter().fold(seeds, |ranges, group| {
+            ranges.map(&group.iter().map(Mapping::piece).collect())
+        });
+        located.min().ok_or(anyhow!("No seed ranges"))
+    }
+
+    /// What `find_min_faster` should get, by trying every seed in turn.
+    #[cfg(test)]
+    fn find_min_slowly(&self) -> Option<usize> {
+        self.seeds
+            .iter()
+            .tuples()
+            .flat_map(|(&start, &count)| start..start + count)
+            .map(|seed| self.map_seed(seed))
+            .min()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::differential;
+    use proptest::{collection::vec, prelude::*};
 
     #[test]
     fn should_parse() -> Result<()> {
@@ -217,9 +207,7 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let almanac = EXAMPLE_INPUT.parse::<Almanac>()?;
-        let mapped = almanac.map_seeds();
-        assert_eq!(&35, mapped.iter().min().unwrap());
+        assert_eq!(35, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -228,19 +216,47 @@ mod tests {
         let test = "seeds: 1 2 3 4\n\nseed-to-soil map:\n2 1 1\n\nsoil-to-whatever map:\n1 2 1";
         let almanac = test.parse::<Almanac>()?;
         let expected = 1;
-        let actual = almanac.find_min_faster();
+        let actual = almanac.find_min_faster()?;
         assert_eq!(expected, actual);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let almanac = EXAMPLE_INPUT.parse::<Almanac>()?;
-        let mapped = almanac.find_min_faster();
-        assert_eq!(46, mapped);
+        assert_eq!(46, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 
+    /// Small almanacs whose mappings don't overlap within a section, like
+    /// the real ones.
+    fn almanac() -> impl Strategy<Value = String> {
+        let seeds = vec((0..50usize, 1..8usize), 1..4);
+        let section = vec((0..5usize, 1..8usize, 0..60usize), 0..4);
+        (seeds, vec(section, 1..4)).prop_map(|(seeds, sections)| {
+            let seeds = seeds.iter().map(|(s, c)| format!("{s} {c}")).join(" ");
+            let sections = sections.iter().enumerate().map(|(i, maps)| {
+                let mut source = 0;
+                let lines = maps.iter().map(|&(gap, len, dest)| {
+                    source += gap;
+                    let line = format!("{dest} {source} {len}");
+                    source += len;
+                    line
+                });
+                format!("section-{i} map:\n{}", lines.format("\n"))
+            });
+            format!("seeds: {seeds}\n\n{}", sections.format("\n\n"))
+        })
+    }
+
+    #[test]
+    fn should_find_min_differentially() {
+        differential::check("day05", almanac(), |input| {
+            let almanac = input.parse::<Almanac>().unwrap();
+            prop_assert_eq!(almanac.find_min_slowly(), almanac.find_min_faster().ok());
+            Ok(())
+        });
+    }
+
     const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
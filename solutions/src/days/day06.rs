@@ -5,7 +5,10 @@ use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 6;
     const TITLE: &'static str = "Wait For It";
 
@@ -13,15 +16,21 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let record = self.input().get_as::<RecordSheet>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let record = input.parse::<RecordSheet>()?;
         Ok(get_win_factor(&record))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let race = self.input().get_as::<Race>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let race = input.parse::<Race>()?;
         Ok(get_win_possibilities(&race))
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<RecordSheet>()?;
+        input.parse::<Race>()?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -140,9 +149,7 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let record = EXAMPLE_INPUT.parse::<RecordSheet>()?;
-        let win_factor = get_win_factor(&record);
-        assert_eq!(288, win_factor);
+        assert_eq!(288, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -156,9 +163,7 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let race = EXAMPLE_INPUT.parse::<Race>()?;
-        let possibilities = get_win_possibilities(&race);
-        assert_eq!(71503, possibilities);
+        assert_eq!(71503, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
@@ -1,11 +1,14 @@
 use std::{cmp::Ordering, collections::HashMap, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{input::lines_as, solver::Solver};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 7;
     const TITLE: &'static str = "Camel Cards";
 
@@ -13,19 +16,25 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let hands = self.input().get_lines_as::<Hand>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let hands = lines_as::<Hand>(input)?;
         Ok(get_winnings(&hands))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let lines = self.input().get_lines()?;
-        let hands = lines
-            .iter()
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let hands = input
+            .lines()
             .filter_map(|l| get_wild_hand(l, 11).ok())
             .collect_vec();
         Ok(get_winnings(&hands))
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        lines_as::<Hand>(input)?;
+        input
+            .lines()
+            .try_for_each(|l| get_wild_hand(l, 11).map(|_| ()))
+    }
 }
 
 fn card_from(c: &char) -> Result<u8> {
@@ -61,10 +70,11 @@ impl FromStr for Hand {
         if halves.len() != 2 {
             return Err(anyhow!("Invalid hand"));
         }
-        let cards = halves[0]
-            .chars()
-            .filter_map(|c| card_from(&c).ok())
-            .collect();
+        let cards = halves[0].chars().map(|c| card_from(&c)).try_collect()?;
+        // Scoring only makes sense for a full hand
+        if halves[0].len() != 5 {
+            return Err(anyhow!("Invalid hand: {s}"));
+        }
         let bid = halves[1].parse()?;
         Ok(Hand {
             cards,
@@ -210,6 +220,8 @@ QQQJA 483";
         let hand = test.parse::<Hand>()?;
         assert_eq!(123, hand.bid);
         assert_eq!(vec![5, 7, 10, 11, 14], hand.cards);
+        assert!("57TJ 123".parse::<Hand>().is_err());
+        assert!("57TJX 123".parse::<Hand>().is_err());
         Ok(())
     }
 
@@ -253,12 +265,7 @@ QQQJA 483";
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let hands = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Hand>().ok())
-            .collect_vec();
-        let winnings = get_winnings(&hands);
-        assert_eq!(6440, winnings);
+        assert_eq!(6440, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -271,12 +278,7 @@ QQQJA 483";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let hands = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| get_wild_hand(l, 11).ok())
-            .collect_vec();
-        let winnings = get_winnings(&hands);
-        assert_eq!(5905, winnings);
+        assert_eq!(5905, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
@@ -1,13 +1,19 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::{common::lcm, solver::Solver};
+use crate::{
+    common::{num::lcm_all, Direction},
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 8;
     const TITLE: &'static str = "Haunted Wasteland";
 
@@ -15,31 +21,19 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
-        Ok(map.travel())
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<Map>()?;
+        map.travel()
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
-        Ok(map.spooky_travel())
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<Map>()?;
+        map.spooky_travel()
     }
-}
 
-#[derive(Debug, PartialEq)]
-enum Direction {
-    Left,
-    Right,
-}
-impl TryFrom<char> for Direction {
-    type Error = anyhow::Error;
-
-    fn try_from(value: char) -> Result<Self> {
-        match value {
-            'L' => Ok(Direction::Left),
-            'R' => Ok(Direction::Right),
-            _ => Err(anyhow!("Invalid direction")),
-        }
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Map>()?;
+        Ok(())
     }
 }
 
@@ -48,6 +42,16 @@ struct Node {
     left: String,
     right: String,
 }
+impl Node {
+    fn follow(&self, dir: &Direction) -> &String {
+        match dir {
+            Direction::Left => &self.left,
+            Direction::Right => &self.right,
+            // The map only ever says left or right
+            Direction::Up | Direction::Down => unreachable!("Can't go {dir:?} from a node"),
+        }
+    }
+}
 
 lazy_static! {
     static ref NODE_RE: Regex = Regex::new(r"(\w+) = \((\w+), (\w+)\)").unwrap();
@@ -65,10 +69,17 @@ impl FromStr for Map {
         if parts.len() != 2 {
             return Err(anyhow!("Invalid map"));
         }
-        let directions = parts[0]
+        let directions: Vec<Direction> = parts[0]
             .chars()
-            .filter_map(|c| c.try_into().ok())
-            .collect_vec();
+            .map(|c| match c {
+                'L' => Ok(Direction::Left),
+                'R' => Ok(Direction::Right),
+                _ => Err(anyhow!("Invalid direction: {c}")),
+            })
+            .try_collect()?;
+        if directions.is_empty() {
+            return Err(anyhow!("No directions to follow"));
+        }
 
         let mut network = HashMap::new();
         for line in parts[1].lines() {
@@ -83,6 +94,15 @@ impl FromStr for Map {
             }
         }
 
+        // Every node has to lead somewhere that's on the map
+        for (id, node) in &network {
+            for next in [&node.left, &node.right] {
+                if !network.contains_key(next) {
+                    return Err(anyhow!("{id} leads to {next}, which isn't on the map"));
+                }
+            }
+        }
+
         Ok(Map {
             directions,
             network,
@@ -90,27 +110,27 @@ impl FromStr for Map {
     }
 }
 impl Map {
-    fn travel(&self) -> usize {
+    fn travel(&self) -> Result<usize> {
         let mut step_count = 0;
         let mut instr = 0;
-        let mut cur = self.network.get("AAA").unwrap();
+        let mut cur = self
+            .network
+            .get("AAA")
+            .ok_or(anyhow!("No AAA to start from"))?;
         loop {
             step_count += 1;
-            let id = match &self.directions[instr] {
-                Direction::Left => &cur.left,
-                Direction::Right => &cur.right,
-            };
+            let id = cur.follow(&self.directions[instr]);
             if id == "ZZZ" {
                 break;
             }
-            cur = self.network.get(id).unwrap();
+            cur = &self.network[id];
             instr = (instr + 1) % self.directions.len();
         }
-        step_count
+        Ok(step_count)
     }
 
     // Not as spooky as the flashbacks to the bus-timetable problem...
-    fn spooky_travel(&self) -> usize {
+    fn spooky_travel(&self) -> Result<usize> {
         let mut step_count = 0;
         let mut instr = 0;
         let mut cur = self
@@ -122,19 +142,13 @@ impl Map {
         loop {
             step_count += 1;
             let dir = &self.directions[instr];
-            let ids = cur
-                .iter()
-                .map(|n| match dir {
-                    Direction::Left => &n.left,
-                    Direction::Right => &n.right,
-                })
-                .collect_vec();
+            let ids = cur.iter().map(|n| n.follow(dir)).collect_vec();
             cur.clear();
             for id in ids {
                 if id.ends_with('Z') {
                     dists.push(step_count);
                 } else {
-                    cur.push(self.network.get(id).unwrap());
+                    cur.push(&self.network[id]);
                 }
             }
             if cur.len() == 0 {
@@ -142,7 +156,7 @@ impl Map {
             }
             instr = (instr + 1) % self.directions.len();
         }
-        lcm(&dists)
+        lcm_all(&dists).ok_or(anyhow!("Step count overflowed"))
     }
 }
 
@@ -168,7 +182,7 @@ XXX = (XXX, XXX)";
 
     #[test]
     fn should_parse() -> Result<()> {
-        let test = "RL\n\nAAA = (BBB, CCC)".parse::<Map>()?;
+        let test = "RL\n\nAAA = (BBB, CCC)\nBBB = (BBB, BBB)\nCCC = (CCC, CCC)".parse::<Map>()?;
         let nodes: HashMap<String, Node> = HashMap::from([(
             "AAA".to_string(),
             Node {
@@ -177,23 +191,31 @@ XXX = (XXX, XXX)";
             },
         )]);
         assert_eq!(vec![Direction::Right, Direction::Left], test.directions);
-        assert_eq!(nodes, test.network);
+        assert_eq!(nodes["AAA"], test.network["AAA"]);
+        assert_eq!(3, test.network.len());
         Ok(())
     }
 
+    #[test]
+    fn should_reject_broken_maps() {
+        let solution = Solution::new();
+        assert!(solution.parse_input("\n\nAAA = (AAA, AAA)").is_err());
+        assert!(solution.parse_input("L\n\nAAA = (BBB, AAA)").is_err());
+        // Only left and right, however else they might be written elsewhere
+        assert!(solution.parse_input("LW\n\nAAA = (AAA, AAA)").is_err());
+        assert!(solution.parse_input("L>\n\nAAA = (AAA, AAA)").is_err());
+        assert!(solution.part_one("L\n\nZZZ = (ZZZ, ZZZ)").is_err());
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let test = EXAMPLE_INPUT_PART1.parse::<Map>()?;
-        let steps = test.travel();
-        assert_eq!(6, steps);
+        assert_eq!(6, Solution::new().part_one(EXAMPLE_INPUT_PART1)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let test = EXAMPLE_INPUT_PART2.parse::<Map>()?;
-        let steps = test.spooky_travel();
-        assert_eq!(6, steps);
+        assert_eq!(6, Solution::new().part_two(EXAMPLE_INPUT_PART2)?);
         Ok(())
     }
 }
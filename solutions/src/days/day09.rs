@@ -1,11 +1,14 @@
 use std::str::FromStr;
 
-use crate::solver::Solver;
-use anyhow::Result;
+use crate::{common::poly::extrapolate, input::lines_as, solver::Solver};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<isize, isize> for Solution {
+impl Solver for Solution {
+    type PartOne = isize;
+    type PartTwo = isize;
+
     const DAY: u8 = 9;
     const TITLE: &'static str = "Mirage Maintenance";
 
@@ -13,16 +16,19 @@ impl Solver<isize, isize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<isize> {
-        let oases = self.input().get_lines_as::<History>()?;
-        let sum = oases.iter().map(|o| o.predict_next()).sum();
-        Ok(sum)
+    fn part_one(&self, input: &str) -> Result<isize> {
+        let oases = lines_as::<History>(input)?;
+        oases.iter().map(|o| o.predict_next()).sum()
+    }
+
+    fn part_two(&self, input: &str) -> Result<isize> {
+        let oases = lines_as::<History>(input)?;
+        oases.iter().map(|o| o.predict_prev()).sum()
     }
 
-    fn part_two(&self) -> Result<isize> {
-        let oases = self.input().get_lines_as::<History>()?;
-        let sum = oases.iter().map(|o| o.predict_prev()).sum();
-        Ok(sum)
+    fn parse_input(&self, input: &str) -> Result<()> {
+        lines_as::<History>(input)?;
+        Ok(())
     }
 }
 
@@ -34,54 +40,37 @@ impl FromStr for History {
 
     fn from_str(s: &str) -> Result<Self> {
         let value = s.split(' ').filter_map(|x| x.parse().ok()).collect_vec();
+        if value.is_empty() {
+            return Err(anyhow!("Empty history: {s}"));
+        }
         Ok(History { value })
     }
 }
 impl History {
-    fn predict_next(&self) -> isize {
-        predict_next(&self.value)
+    fn values(&self) -> Vec<i128> {
+        self.value.iter().map(|&v| v as i128).collect()
     }
 
-    fn predict_prev(&self) -> isize {
-        predict_next(&self.value.iter().rev().cloned().collect_vec())
+    /// The value at index `n`, if it fits.
+    fn predict(&self, n: i128) -> Result<isize> {
+        extrapolate(&self.values(), n)
+            .and_then(|v| isize::try_from(v).ok())
+            .ok_or(anyhow!("Prediction too big for {:?}", self.value))
     }
-}
 
-fn predict_next(value: &[isize]) -> isize {
-    if value.len() == 1 {
-        return value[0];
+    fn predict_next(&self) -> Result<isize> {
+        self.predict(self.value.len() as i128)
     }
-    let mut diffs: Vec<isize> = vec![];
-    let mut next_diffs: Vec<isize> = vec![];
-    let mut increments: Vec<isize> = vec![];
-    let mut latest = 0;
-    let mut all_zero = true;
-    diffs.extend(value.iter());
-    loop {
-        for i in 0..diffs.len() - 1 {
-            let j = i + 1;
-            latest = diffs[j] - diffs[i];
-            next_diffs.push(latest);
-            if latest != 0 {
-                all_zero = false;
-            }
-        }
-        increments.push(latest);
-        diffs.clear();
-        diffs.append(&mut next_diffs);
-        if all_zero {
-            break;
-        }
-        all_zero = true;
+
+    fn predict_prev(&self) -> Result<isize> {
+        self.predict(-1)
     }
-    let max = value[value.len() - 1];
-    let incr: isize = increments.iter().sum();
-    max + incr
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::{collection::vec, prelude::*};
 
     const EXAMPLE_INPUT: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
@@ -97,36 +86,44 @@ mod tests {
     #[test]
     fn should_get_next() -> Result<()> {
         let test = "1 2 3".parse::<History>()?;
-        assert_eq!(4, test.predict_next());
+        assert_eq!(4, test.predict_next()?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let hists = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<History>().ok())
-            .collect_vec();
-        let total: isize = hists.iter().map(|h| h.predict_next()).sum();
-        assert_eq!(114, total);
+        assert_eq!(114, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_get_prev() -> Result<()> {
         let test = "1 2 3".parse::<History>()?;
-        assert_eq!(0, test.predict_prev());
+        assert_eq!(0, test.predict_prev()?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let hists = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<History>().ok())
-            .collect_vec();
-        let total: isize = hists.iter().map(|h| h.predict_prev()).sum();
-        assert_eq!(2, total);
+        assert_eq!(2, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_predict_prev_as_next_of_reversed(value in vec(-1_000_000isize..1_000_000, 1..=21)) {
+            let reversed = History { value: value.iter().rev().copied().collect() };
+            let history = History { value };
+            prop_assert_eq!(history.predict_prev().ok(), reversed.predict_next().ok());
+            prop_assert_eq!(history.predict_next().ok(), reversed.predict_prev().ok());
+        }
+
+        #[test]
+        fn should_continue_polynomials(coefficients in vec(-100isize..100, 1..5), len in 5isize..=21) {
+            let p = |x: isize| coefficients.iter().rev().fold(0, |acc, c| acc * x + c);
+            let history = History { value: (0..len).map(p).collect() };
+            prop_assert_eq!(Some(p(len)), history.predict_next().ok());
+            prop_assert_eq!(Some(p(-1)), history.predict_prev().ok());
+        }
+    }
 }
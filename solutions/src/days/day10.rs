@@ -3,11 +3,20 @@ use std::{
     str::FromStr,
 };
 
-use crate::solver::Solver;
+use crate::{
+    common::{polygon::Polygon, search::bfs_distances, Direction, IPoint},
+    solver::Solver,
+    viz::{Cell, Frame, Visualizer},
+};
 use anyhow::{anyhow, Result};
+use colored::Color;
+use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 10;
     const TITLE: &'static str = "Pipe Maze";
 
@@ -15,52 +24,27 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let maze = self.input().get_as::<Maze>()?;
-        Ok(maze.get_max_depth())
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let maze = input.parse::<Maze>()?;
+        maze.get_max_depth()
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let maze = self.input().get_as::<Maze>()?;
-        Ok(maze.get_interior_tiles())
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let maze = input.parse::<Maze>()?;
+        maze.get_interior_tiles()
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-impl From<(isize, isize)> for Point {
-    fn from(value: (isize, isize)) -> Self {
-        Point {
-            x: value.0,
-            y: value.1,
-        }
-    }
-}
-impl std::ops::Add for Point {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self {
-        Point {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Maze>()?;
+        Ok(())
     }
-}
-impl Point {
-    fn new(x: isize, y: isize) -> Self {
-        Point { x, y }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        let maze = input.parse::<Maze>()?;
+        maze.animate(viz)
     }
 }
 
-const CARDINALS: [Point; 4] = [
-    Point { x: 0, y: -1 },
-    Point { x: 1, y: 0 },
-    Point { x: 0, y: 1 },
-    Point { x: -1, y: 0 },
-];
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Pipe {
     Vertical,
@@ -86,20 +70,32 @@ impl TryFrom<char> for Pipe {
     }
 }
 impl Pipe {
-    fn get_offsets(&self) -> (Point, Point) {
+    fn get_directions(&self) -> (Direction, Direction) {
         match self {
-            Pipe::Vertical => (Point::new(0, -1), Point::new(0, 1)),
-            Pipe::Horizontal => (Point::new(-1, 0), Point::new(1, 0)),
-            Pipe::BendNE => (Point::new(0, -1), Point::new(1, 0)),
-            Pipe::BendNW => (Point::new(0, -1), Point::new(-1, 0)),
-            Pipe::BendSE => (Point::new(0, 1), Point::new(1, 0)),
-            Pipe::BendSW => (Point::new(0, 1), Point::new(-1, 0)),
+            Pipe::Vertical => (Direction::Up, Direction::Down),
+            Pipe::Horizontal => (Direction::Left, Direction::Right),
+            Pipe::BendNE => (Direction::Up, Direction::Right),
+            Pipe::BendNW => (Direction::Up, Direction::Left),
+            Pipe::BendSE => (Direction::Down, Direction::Right),
+            Pipe::BendSW => (Direction::Down, Direction::Left),
         }
     }
 
-    fn get_connecting(&self, start: Point) -> (Point, Point) {
-        let (a, b) = self.get_offsets();
-        (a + start, b + start)
+    fn get_connecting(&self, start: IPoint) -> (IPoint, IPoint) {
+        let (a, b) = self.get_directions();
+        (a.offset() + start, b.offset() + start)
+    }
+
+    /// The pipe drawn with box-drawing characters, which join up properly.
+    fn to_char(self) -> char {
+        match self {
+            Pipe::Vertical => '│',
+            Pipe::Horizontal => '─',
+            Pipe::BendNE => '└',
+            Pipe::BendNW => '┘',
+            Pipe::BendSE => '┌',
+            Pipe::BendSW => '┐',
+        }
     }
 }
 
@@ -125,171 +121,159 @@ impl TryFrom<char> for Tile {
 }
 
 struct PipeLoop {
-    the_loop: HashSet<Point>,
     depth: usize,
-    min: (isize, isize),
-    max: (isize, isize),
     start_pipe: Pipe,
 }
 
 struct Maze {
-    tiles: HashMap<Point, Tile>,
-    start: Point,
-    width: usize,
-    height: usize,
+    tiles: HashMap<IPoint, Tile>,
+    start: IPoint,
 }
 impl FromStr for Maze {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
         let mut tiles = HashMap::new();
-        let mut start = Point::new(0, 0);
-        let mut width = 0;
-        let mut height = 0;
+        let mut start = None;
         for (y, line) in s.lines().enumerate() {
-            height = y + 1;
             for (x, c) in line.chars().enumerate() {
-                width = x + 1;
                 let tile = Tile::try_from(c)?;
-                let coord = Point::new(x as isize, y as isize);
+                let coord = IPoint::new(x as isize, y as isize);
                 tiles.insert(coord, tile);
                 if tile == Tile::Start {
-                    start = coord;
+                    start = Some(coord);
                 }
             }
         }
-        Ok(Maze {
-            tiles,
-            start,
-            width,
-            height,
-        })
+        let start = start.ok_or(anyhow!("The maze has no start"))?;
+        Ok(Maze { tiles, start })
     }
 }
 impl Maze {
-    fn get_loop(&self) -> PipeLoop {
-        let mut depth = 0;
-        let mut the_loop: HashSet<Point> = HashSet::new();
-        let mut positions = vec![];
-        let mut offsets = vec![];
-        let mut min_x = self.start.x;
-        let mut min_y = self.start.y;
-        let mut max_x = self.start.x;
-        let mut max_y = self.start.y;
-
+    fn get_loop(&self) -> Result<PipeLoop> {
         // Get starting branches
-        the_loop.insert(self.start);
-        for offset in CARDINALS {
-            let test = self.start + offset;
-            if let Some(Tile::Pipe(p)) = self.tiles.get(&test) {
-                let (a, b) = p.get_connecting(test);
-                if a == self.start || b == self.start {
-                    positions.push((test, *p));
-                    offsets.push(offset);
+        let directions = Direction::ALL
+            .into_iter()
+            .filter(|&dir| {
+                let test = self.start + dir.offset();
+                match self.tiles.get(&test) {
+                    Some(Tile::Pipe(p)) => {
+                        let (a, b) = p.get_connecting(test);
+                        a == self.start || b == self.start
+                    }
+                    _ => false,
                 }
-            }
+            })
+            .collect_vec();
+        if directions.len() != 2 {
+            return Err(anyhow!("Starting point must have two connecting pipes!"));
         }
 
-        let start_pipe = match (offsets[0], offsets[1]) {
-            (Point { x: 0, y: -1 }, Point { x: 0, y: 1 }) => Pipe::Vertical,
-            (Point { x: 0, y: -1 }, Point { x: -1, y: 0 }) => Pipe::BendNW,
-            (Point { x: 0, y: -1 }, Point { x: 1, y: 0 }) => Pipe::BendNE,
-            (Point { x: 1, y: 0 }, Point { x: 0, y: 1 }) => Pipe::BendSE,
-            (Point { x: 1, y: 0 }, Point { x: -1, y: 0 }) => Pipe::Horizontal,
-            (Point { x: 0, y: 1 }, Point { x: -1, y: 0 }) => Pipe::BendSW,
+        // Direction::ALL runs clockwise from up, so the pairs come out in order
+        let start_pipe = match (directions[0], directions[1]) {
+            (Direction::Up, Direction::Down) => Pipe::Vertical,
+            (Direction::Up, Direction::Left) => Pipe::BendNW,
+            (Direction::Up, Direction::Right) => Pipe::BendNE,
+            (Direction::Right, Direction::Down) => Pipe::BendSE,
+            (Direction::Right, Direction::Left) => Pipe::Horizontal,
+            (Direction::Down, Direction::Left) => Pipe::BendSW,
             _ => Pipe::Horizontal,
         };
 
-        if positions.len() != 2 {
-            panic!("Starting point must have two connecting pipes!");
-        }
-        loop {
-            depth += 1;
-            let mut new_pos = vec![];
-            for &(pos, pipe) in &positions {
-                the_loop.insert(pos);
-                if pos.x < min_x {
-                    min_x = pos.x;
-                }
-                if pos.y < min_y {
-                    min_y = pos.y;
-                }
-                if pos.x > max_x {
-                    max_x = pos.x;
-                }
-                if pos.y > max_y {
-                    max_y = pos.y;
-                }
-                let (a, b) = pipe.get_connecting(pos);
-                for next in [a, b] {
-                    if the_loop.contains(&next) {
-                        continue;
-                    }
-                    if let Some(Tile::Pipe(p)) = self.tiles.get(&next) {
-                        new_pos.push((next, *p));
-                    }
-                }
-            }
-            if new_pos.len() == 0 {
-                break;
-            }
-            positions.clear();
-            positions.append(&mut new_pos);
-        }
-        PipeLoop {
-            the_loop,
-            depth,
-            min: (min_x, min_y),
-            max: (max_x, max_y),
-            start_pipe,
+        // Both ways round the loop meet at the far side, which is as deep as it gets
+        let distances = bfs_distances(self.start, |&pos| {
+            let pipe = match self.tiles.get(&pos) {
+                Some(&Tile::Pipe(p)) => p,
+                _ => start_pipe,
+            };
+            let (a, b) = pipe.get_connecting(pos);
+            [a, b]
+                .into_iter()
+                .filter(|next| matches!(self.tiles.get(next), Some(Tile::Pipe(_) | Tile::Start)))
+                .collect_vec()
+        });
+        let depth = distances.values().copied().max().unwrap_or(0);
+        Ok(PipeLoop { depth, start_pipe })
+    }
+
+    fn get_max_depth(&self) -> Result<usize> {
+        Ok(self.get_loop()?.depth)
+    }
+
+    /// Walks once round the loop from the start, returning every tile on it in order.
+    /// Every pipe along the way has to connect back to the one before it, so
+    /// the walk can only end up back at the start (or at a dead end).
+    fn trace_loop(&self, start_pipe: Pipe) -> Result<Vec<IPoint>> {
+        let mut tiles = vec![self.start];
+        let (mut prev, mut cur) = (self.start, start_pipe.get_connecting(self.start).0);
+        while cur != self.start {
+            let next = match self.tiles.get(&cur) {
+                Some(&Tile::Pipe(p)) => match p.get_connecting(cur) {
+                    (a, b) if a == prev => Some(b),
+                    (a, b) if b == prev => Some(a),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let next = next.ok_or(anyhow!("The loop isn't closed at {cur:?}"))?;
+            tiles.push(cur);
+            (prev, cur) = (cur, next);
         }
+        Ok(tiles)
     }
 
-    fn get_max_depth(&self) -> usize {
-        self.get_loop().depth
+    fn get_interior_tiles(&self) -> Result<usize> {
+        let PipeLoop { start_pipe, .. } = self.get_loop()?;
+        Ok(Polygon::new(self.trace_loop(start_pipe)?).interior_points())
     }
 
-    fn get_interior_tiles(&self) -> usize {
-        let PipeLoop {
-            depth: _,
-            the_loop,
-            min: (min_x, min_y),
-            max: (max_x, max_y),
-            start_pipe,
-        } = self.get_loop();
-
-        let mut interior = 0;
-        for y in 0..(self.height as isize) {
-            let mut out = true;
-            for x in 0..(self.width as isize) {
-                let pos = Point::new(x, y);
-                if the_loop.contains(&pos) {
-                    let pipe = if pos == self.start {
-                        start_pipe
-                    } else {
-                        // we need to treat the starting tile as its equivalent pipe to count correctly
-                        match self.tiles.get(&pos) {
-                            Some(&Tile::Pipe(p)) => p,
-                            _ => panic!("wtf"),
-                        }
-                    };
-                    match pipe {
-                        // only count connections from above to elsewhere (below/side)
-                        Pipe::Vertical | Pipe::BendNE | Pipe::BendNW => out = !out,
-                        _ => (),
-                    }
-                } else if !out && min_x < x && x < max_x && min_y < y && y < max_y {
-                    interior += 1;
-                }
+    /// Shows the loop being traced out from the start, then everything it encloses.
+    fn animate(&self, viz: &Visualizer) -> Result<()> {
+        let width = self.tiles.keys().map(|p| p.x + 1).max().unwrap_or(0);
+        let height = self.tiles.keys().map(|p| p.y + 1).max().unwrap_or(0);
+        let mut frame = Frame::new(width as usize, height as usize);
+        let PipeLoop { start_pipe, .. } = self.get_loop()?;
+        let pipe_at = |pos| match self.tiles.get(&pos) {
+            Some(&Tile::Pipe(p)) => p,
+            _ => start_pipe,
+        };
+        for (&pos, &tile) in &self.tiles {
+            let c = match tile {
+                Tile::Ground => '.',
+                _ => pipe_at(pos).to_char(),
+            };
+            frame.set(pos, Cell::colored(c, Color::BrightBlack));
+        }
+
+        let path = self.trace_loop(start_pipe)?;
+        // Long loops would take forever a tile at a time
+        let stride = (path.len() / 200).max(1);
+        for (i, &pos) in path.iter().enumerate() {
+            let cell = Cell::colored(pipe_at(pos).to_char(), Color::BrightGreen).bold();
+            frame.set(pos, cell);
+            if (i + 1) % stride == 0 || i + 1 == path.len() {
+                viz.show(|| frame.clone().with_caption(format!("{} tiles round", i + 1)));
             }
         }
-        interior
+
+        let on_loop: HashSet<_> = path.iter().copied().collect();
+        let polygon = Polygon::new(path);
+        let mut inside = 0;
+        for &pos in self.tiles.keys() {
+            if !on_loop.contains(&pos) && polygon.contains(&pos) {
+                frame.set(pos, Cell::colored('I', Color::BrightYellow).bold());
+                inside += 1;
+            }
+        }
+        viz.show(|| frame.with_caption(format!("{inside} tiles inside the loop")));
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{cell::RefCell, rc::Rc};
 
     const EXAMPLE_INPUT_PART1: &str = "..F7.
 .FJ|.
@@ -311,34 +295,62 @@ L7JLJL-JLJLJL--JLJ.L";
     fn should_parse() -> Result<()> {
         let test = ".S-.".parse::<Maze>()?;
         let expected = HashMap::from_iter(vec![
-            (Point::new(0, 0), Tile::Ground),
-            (Point::new(1, 0), Tile::Start),
-            (Point::new(2, 0), Tile::Pipe(Pipe::Horizontal)),
-            (Point::new(3, 0), Tile::Ground),
+            (IPoint::new(0, 0), Tile::Ground),
+            (IPoint::new(1, 0), Tile::Start),
+            (IPoint::new(2, 0), Tile::Pipe(Pipe::Horizontal)),
+            (IPoint::new(3, 0), Tile::Ground),
         ]);
         assert_eq!(expected, test.tiles);
-        assert_eq!(Point::new(1, 0), test.start);
+        assert_eq!(IPoint::new(1, 0), test.start);
         Ok(())
     }
 
     #[test]
     fn should_get_depth() -> Result<()> {
         let test = ".-S--".parse::<Maze>()?;
-        assert_eq!(2, test.get_max_depth());
+        assert_eq!(2, test.get_max_depth()?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_broken_loops() -> Result<()> {
+        let solution = Solution::new();
+        assert_eq!(1, solution.part_two("S-7\n|.|\nL-J")?);
+        // Runs into the ground
+        assert!(solution.part_two("S-7\n|..\nL-J").is_err());
+        // Runs into a pipe that doesn't connect back
+        assert!(solution.part_two("S-7\n|.-\nL-J").is_err());
+        assert!(solution.part_two(".-S--").is_err());
+        assert!(solution.part_two(".S.").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_animate() -> Result<()> {
+        let maze = "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF".parse::<Maze>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        maze.animate(&Visualizer::new(frames.clone()))?;
+        let frames = frames.borrow();
+        assert_eq!(9, frames.len());
+        assert_eq!("8 tiles round", frames[7].caption());
+        let last = frames.last().unwrap();
+        assert_eq!(
+            vec!["─└│┌┐", "┐┌─┐│", "└│I││", "─└─┘│", "└│─┘┌"],
+            last.plain_lines()
+        );
+        assert_eq!("1 tiles inside the loop", last.caption());
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let maze = EXAMPLE_INPUT_PART1.parse::<Maze>()?;
-        assert_eq!(8, maze.get_max_depth());
+        assert_eq!(8, Solution::new().part_one(EXAMPLE_INPUT_PART1)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let maze = EXAMPLE_INPUT_PART2.parse::<Maze>()?;
-        assert_eq!(10, maze.get_interior_tiles());
+        assert_eq!(10, Solution::new().part_two(EXAMPLE_INPUT_PART2)?);
         Ok(())
     }
 }
@@ -1,11 +1,14 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{common::IPoint, solver::Solver};
 use anyhow::Result;
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 11;
     const TITLE: &'static str = "Cosmic Expansion";
 
@@ -13,21 +16,26 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<Map>()?;
         let expanded = map.expand(2);
         Ok(expanded.get_min_distances())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<Map>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<Map>()?;
         let expanded = map.expand(1_000_000);
         Ok(expanded.get_min_distances())
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Map>()?;
+        Ok(())
+    }
 }
 
 struct Map {
-    galaxies: Vec<(isize, isize)>,
+    galaxies: Vec<IPoint>,
     empty_rows: Vec<usize>,
     empty_cols: Vec<usize>,
 }
@@ -42,7 +50,7 @@ impl FromStr for Map {
             for (x, c) in line.chars().enumerate() {
                 let mut empty_space = true;
                 if c == '#' {
-                    galaxies.push((x as isize, y as isize));
+                    galaxies.push(IPoint::new(x as isize, y as isize));
                     empty_space = false;
                 }
                 rows_empty
@@ -80,23 +88,23 @@ impl Map {
         let mut row_expansions = HashMap::new();
         let mut col_expansions = HashMap::new();
         for g in &self.galaxies {
-            let expand_x = *col_expansions.entry(g.0).or_insert(
+            let expand_x = *col_expansions.entry(g.x).or_insert(
                 (self
                     .empty_cols
                     .iter()
-                    .filter(|&v| v < &(g.0 as usize))
+                    .filter(|&v| v < &(g.x as usize))
                     .count()
                     * (factor - 1)) as isize,
             );
-            let expand_y = *row_expansions.entry(g.1).or_insert(
+            let expand_y = *row_expansions.entry(g.y).or_insert(
                 (self
                     .empty_rows
                     .iter()
-                    .filter(|&v| v < &(g.1 as usize))
+                    .filter(|&v| v < &(g.y as usize))
                     .count()
                     * (factor - 1)) as isize,
             );
-            galaxies.push((g.0 + expand_x, g.1 + expand_y));
+            galaxies.push(IPoint::new(g.x + expand_x, g.y + expand_y));
         }
         Map {
             galaxies,
@@ -108,11 +116,10 @@ impl Map {
     fn get_min_distances(&self) -> usize {
         let mut total_min_distance: usize = 0;
         for i in 0..self.galaxies.len() - 1 {
-            let (lx, ly) = self.galaxies[i];
+            let l = self.galaxies[i];
             for j in i + 1..self.galaxies.len() {
-                let (rx, ry) = self.galaxies[j];
-                let distance: usize = ((lx - rx).abs() + (ly - ry).abs()).try_into().unwrap();
-                total_min_distance += distance;
+                let r = self.galaxies[j];
+                total_min_distance += l.manhattan(&r);
             }
         }
         total_min_distance
@@ -137,7 +144,7 @@ mod tests {
     #[test]
     fn should_parse() -> Result<()> {
         let test = "..#..".parse::<Map>()?;
-        let expected_galaxies = vec![(2, 0)];
+        let expected_galaxies = vec![IPoint::new(2, 0)];
         let expected_cols = vec![0, 1, 3, 4];
         let expected_rows: Vec<usize> = vec![];
         assert_eq!(expected_galaxies, test.galaxies);
@@ -149,9 +156,9 @@ mod tests {
     #[test]
     fn should_expand() -> Result<()> {
         let test = ".#.".parse::<Map>()?;
-        assert_eq!(vec![(1, 0)], test.galaxies);
+        assert_eq!(vec![IPoint::new(1, 0)], test.galaxies);
         let expanded = test.expand(2);
-        assert_eq!(vec![(2, 0)], expanded.galaxies);
+        assert_eq!(vec![IPoint::new(2, 0)], expanded.galaxies);
         Ok(())
     }
 
@@ -164,9 +171,7 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let map = EXAMPLE_INPUT.parse::<Map>()?;
-        let expanded = map.expand(2);
-        assert_eq!(374, expanded.get_min_distances());
+        assert_eq!(374, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -1,11 +1,18 @@
 use std::str::FromStr;
 
-use crate::solver::Solver;
+use crate::{
+    common::memo::{Memo, MemoStats},
+    input::lines_as,
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 12;
     const TITLE: &'static str = "Hot Springs";
 
@@ -13,13 +20,34 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let records = self.input().get_lines_as::<Record>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let records = lines_as::<Record>(input)?;
         Ok(records.iter().map(|r| r.get_valid_permutations()).sum())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        Ok(0)
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let records = lines_as::<Record>(input)?;
+        Ok(records
+            .iter()
+            .map(|r| r.unfold(5).get_valid_permutations())
+            .sum())
+    }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        lines_as::<Record>(input)?;
+        Ok(())
+    }
+
+    fn part_stats(&self, part: u8, input: &str) -> Result<Option<String>> {
+        let records = lines_as::<Record>(input)?;
+        let stats: MemoStats = records
+            .iter()
+            .map(|r| match part {
+                1 => r.count_with_stats().1,
+                _ => r.unfold(5).count_with_stats().1,
+            })
+            .sum();
+        Ok(Some(format!("arrangement cache: {stats}")))
     }
 }
 
@@ -69,76 +97,95 @@ impl FromStr for Record {
     }
 }
 impl Record {
+    #[cfg(test)]
     fn is_valid(&self) -> bool {
         is_valid_record(&self.springs, &self.damaged_groups)
     }
 
     fn get_valid_permutations(&self) -> usize {
-        if self.is_valid() {
-            return 1;
-        }
-        let mut valid = 0;
-        let permutations = get_permutations(&self.springs);
-        match permutations {
-            Some(perms) => {
-                for perm in &perms {
-                    if is_valid_record(&perm, &self.damaged_groups) {
-                        valid += 1;
+        self.count_with_stats().0
+    }
+
+    fn count_with_stats(&self) -> (usize, MemoStats) {
+        let mut memo = Memo::new();
+        let count = self.count_arrangements(&mut memo, 0, 0);
+        (count, memo.stats())
+    }
+
+    /// How many ways the springs from `spring` onwards can be filled in to
+    /// match the damaged groups from `group` onwards.
+    fn count_arrangements(
+        &self,
+        memo: &mut Memo<(usize, usize), usize>,
+        spring: usize,
+        group: usize,
+    ) -> usize {
+        memo.get_or_insert_with((spring, group), |memo| {
+            let Some(&next) = self.springs.get(spring) else {
+                return (group == self.damaged_groups.len()) as usize;
+            };
+            let mut total = 0;
+            if next != Spring::Damaged {
+                total += self.count_arrangements(memo, spring + 1, group);
+            }
+            if next != Spring::Operational {
+                if let Some(&size) = self.damaged_groups.get(group) {
+                    // The whole group has to fit, with something other than
+                    // another damaged spring straight after it
+                    let end = spring + size;
+                    let fits = end <= self.springs.len()
+                        && !self.springs[spring..end].contains(&Spring::Operational)
+                        && self.springs.get(end) != Some(&Spring::Damaged);
+                    if fits {
+                        total += self.count_arrangements(memo, end + 1, group + 1);
                     }
                 }
             }
-            None => (),
-        }
-        valid
+            total
+        })
     }
-}
 
-fn get_permutations(springs: &[Spring]) -> Option<Vec<Vec<Spring>>> {
-    let mut unknown: Option<usize> = None;
-    for (i, s) in springs.iter().enumerate() {
-        match s {
-            Spring::Unknown => {
-                unknown = Some(i);
-                break;
-            }
-            _ => (),
-        }
-    }
-    match unknown {
-        Some(idx) => {
-            let after = idx + 1;
-            let base = &springs[..idx];
-            let mut ret = vec![];
-            let mut left = Vec::from_iter(base.iter().cloned());
-            let mut right = Vec::from_iter(base.iter().cloned());
-            left.push(Spring::Operational);
-            right.push(Spring::Damaged);
-            if after < springs.len() {
-                for next in [left, right] {
-                    let perms = get_permutations(&springs[after..]);
-                    match perms {
-                        Some(others) => {
-                            for o in others {
-                                let mut permutation = next.clone();
-                                permutation.extend(o.iter());
-                                ret.push(permutation);
+    /// What `get_valid_permutations` should get, by trying every way of
+    /// filling in the unknown springs.
+    #[cfg(test)]
+    fn count_by_brute_force(&self) -> usize {
+        let unknowns = self
+            .springs
+            .iter()
+            .filter(|&&s| s == Spring::Unknown)
+            .count();
+        (0..1usize << unknowns)
+            .filter(|guess| {
+                let mut bit = 0;
+                let filled = self
+                    .springs
+                    .iter()
+                    .map(|&s| match s {
+                        Spring::Unknown => {
+                            bit += 1;
+                            match guess >> (bit - 1) & 1 {
+                                1 => Spring::Damaged,
+                                _ => Spring::Operational,
                             }
                         }
-                        None => {
-                            ret.push(next.iter().chain(&springs[after..]).cloned().collect_vec());
-                        }
-                    }
-                }
-            } else {
-                ret.push(left);
-                ret.push(right);
-            }
-            Some(ret)
+                        known => known,
+                    })
+                    .collect_vec();
+                is_valid_record(&filled, &self.damaged_groups)
+            })
+            .count()
+    }
+
+    fn unfold(&self, times: usize) -> Self {
+        let springs = vec![self.springs.clone(); times];
+        Record {
+            springs: springs.join(&Spring::Unknown),
+            damaged_groups: self.damaged_groups.repeat(times),
         }
-        None => None,
     }
 }
 
+#[cfg(test)]
 fn is_valid_record(springs: &[Spring], damaged_groups: &[usize]) -> bool {
     let mut damaged = vec![];
     let mut group: Option<usize> = None;
@@ -170,6 +217,8 @@ fn is_valid_record(springs: &[Spring], damaged_groups: &[usize]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::differential;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "???.### 1,1,3
 .??..??...?##. 1,1,3
@@ -212,14 +261,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_report_cache_stats() -> Result<()> {
+        let (count, stats) = "???.### 1,1,3".parse::<Record>()?.count_with_stats();
+        assert_eq!(1, count);
+        assert!(stats.misses > 0);
+        let report = Solution::new().part_stats(2, EXAMPLE_INPUT)?.unwrap();
+        assert!(report.starts_with("arrangement cache: "));
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let records = EXAMPLE_INPUT
-            .lines()
-            .filter_map(|l| l.parse::<Record>().ok())
-            .collect_vec();
-        let valid: usize = records.iter().map(|r| r.get_valid_permutations()).sum();
-        assert_eq!(21, valid);
+        assert_eq!(21, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
+
+    #[test]
+    fn should_unfold() -> Result<()> {
+        let test = ".# 1".parse::<Record>()?.unfold(2);
+        assert_eq!(".#?.# 1,1".parse::<Record>()?.springs, test.springs);
+        assert_eq!(vec![1, 1], test.damaged_groups);
+        Ok(())
+    }
+
+    #[test]
+    fn should_solve_part2() -> Result<()> {
+        assert_eq!(525152, Solution::new().part_two(EXAMPLE_INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_count_arrangements_differentially() {
+        let record = ("[.#?]{0,12}", proptest::collection::vec(1..5usize, 0..4));
+        let input =
+            record.prop_map(|(springs, groups)| format!("{springs} {}", groups.iter().join(",")));
+        differential::check("day12", input, |input| {
+            let record = input.parse::<Record>().unwrap();
+            prop_assert_eq!(
+                record.count_by_brute_force(),
+                record.get_valid_permutations()
+            );
+            // Unfolding doubles the unknowns, so only try it on short records
+            if record.springs.len() <= 6 {
+                let unfolded = record.unfold(2);
+                prop_assert_eq!(
+                    unfolded.count_by_brute_force(),
+                    unfolded.get_valid_permutations()
+                );
+            }
+            Ok(())
+        });
+    }
 }
@@ -1,10 +1,13 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::{common::Point, solver::Solver};
+use crate::{common::Point, input::grouped_as, solver::Solver};
 use anyhow::{anyhow, Result};
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 13;
     const TITLE: &'static str = "Point of Incidence";
 
@@ -12,15 +15,20 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let patches = self.input().get_grouped_as::<Patch>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let patches = grouped_as::<Patch>(input)?;
         Ok(summarize(&patches, 0))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let patches = self.input().get_grouped_as::<Patch>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let patches = grouped_as::<Patch>(input)?;
         Ok(summarize(&patches, 1))
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        grouped_as::<Patch>(input)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -58,6 +66,11 @@ impl FromStr for Patch {
         let mut points = HashMap::new();
         let mut max_x = 0;
         let mut max_y = 0;
+        // Every row gets compared against every other, so they must line up
+        let width = s.lines().next().map_or(0, |l| l.chars().count());
+        if width == 0 || s.lines().any(|l| l.chars().count() != width) {
+            return Err(anyhow!("Invalid patch: {s}"));
+        }
         for (y, line) in s.lines().enumerate() {
             max_y = y;
             for (x, char) in line.chars().enumerate() {
@@ -169,9 +182,24 @@ fn summarize(patches: &[Patch], tolerance: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use itertools::Itertools;
+    use proptest::{collection::vec, prelude::*};
 
-    use super::*;
+    /// Small patches of ash and rock, as rows of text.
+    fn patch() -> impl Strategy<Value = Vec<String>> {
+        (1usize..10, 1usize..10).prop_flat_map(|(width, height)| {
+            let row = vec(prop_oneof![Just('.'), Just('#')], width);
+            vec(row.prop_map(String::from_iter), height)
+        })
+    }
+
+    fn transpose(rows: &[String]) -> Vec<String> {
+        let grid = rows.iter().map(|r| r.chars().collect_vec()).collect_vec();
+        (0..grid[0].len())
+            .map(|x| grid.iter().map(|row| row[x]).collect())
+            .collect()
+    }
 
     const EXAMPLE_INPUT: &str = "#.##..##.
 ..#.##.#.
@@ -215,23 +243,36 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let patches = EXAMPLE_INPUT
-            .split("\n\n")
-            .filter_map(|s| s.parse::<Patch>().ok())
-            .collect_vec();
-        let summary = summarize(&patches, 0);
-        assert_eq!(405, summary);
+        assert_eq!(405, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let patches = EXAMPLE_INPUT
-            .split("\n\n")
-            .filter_map(|s| s.parse::<Patch>().ok())
-            .collect_vec();
-        let summary = summarize(&patches, 1);
-        assert_eq!(400, summary);
+        assert_eq!(400, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_reflect_the_same_when_transposed(rows in patch(), tolerance in 0usize..2) {
+            let patch = rows.join("\n").parse::<Patch>().unwrap();
+            let transposed = transpose(&rows).join("\n").parse::<Patch>().unwrap();
+            prop_assert_eq!(
+                reflection_check(&patch, patch.max_x, Patch::compare_cols, tolerance),
+                reflection_check(&transposed, transposed.max_y, Patch::compare_rows, tolerance)
+            );
+            prop_assert_eq!(
+                reflection_check(&patch, patch.max_y, Patch::compare_rows, tolerance),
+                reflection_check(&transposed, transposed.max_x, Patch::compare_cols, tolerance)
+            );
+        }
+
+        #[test]
+        fn should_find_a_reflection_in_a_mirrored_patch(rows in patch()) {
+            let mirrored = rows.iter().chain(rows.iter().rev()).join("\n");
+            let patch = mirrored.parse::<Patch>().unwrap();
+            prop_assert!(patch.get_reflection(0).is_some());
+        }
+    }
 }
@@ -1,17 +1,24 @@
 use std::{cmp::Ordering, collections::HashSet, str::FromStr};
 
-use crate::{common::Point, solver::Solver};
+use crate::{
+    common::{cycle::nth_state, Direction, IPoint, Point},
+    progress::Progress,
+    solver::Solver,
+    viz::{Cell, Frame, Visualizer},
+};
 use anyhow::{anyhow, Result};
+use colored::Color;
 use itertools::Itertools;
 
-// Ok, I definitely got lucky while playing around with different values here.
-// I guess by coincidence the pattern in my input happens to repeat exactly
-// every 1,000 iterations. I *should* come back and do proper cycle detection
-// like I wanted to, but this is the code that got me the gold star, so...
-const MAX_CYCLES: usize = 1_000;
+const SPIN_CYCLES: usize = 1_000_000_000;
+// After a few it's hard to tell one spin from the next
+const VISUALIZED_SPINS: usize = 3;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 14;
     const TITLE: &'static str = "Parabolic Reflector Dish";
 
@@ -19,17 +26,32 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let mut platform = self.input().get_as::<Platform>()?;
-        platform.roll(Direction::North);
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let mut platform = input.parse::<Platform>()?;
+        platform.roll(Direction::Up);
         Ok(platform.get_load())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let mut platform = self.input().get_as::<Platform>()?;
-        platform.spin(MAX_CYCLES);
+    fn part_two(&self, input: &str) -> Result<usize> {
+        self.part_two_with_progress(input, &Progress::hidden())
+    }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Platform>()?;
+        Ok(())
+    }
+
+    fn part_two_with_progress(&self, input: &str, progress: &Progress) -> Result<usize> {
+        let mut platform = input.parse::<Platform>()?;
+        platform.spin(SPIN_CYCLES, progress);
         Ok(platform.get_load())
     }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        let mut platform = input.parse::<Platform>()?;
+        platform.animate(VISUALIZED_SPINS, viz);
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,14 +71,6 @@ impl TryFrom<char> for Rock {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
 #[derive(Debug)]
 struct Platform {
     round_rocks: HashSet<Point>,
@@ -107,31 +121,31 @@ impl Platform {
     fn roll(&mut self, dir: Direction) {
         let mut rolled = HashSet::new();
         let comp: fn(&&Point, &&Point) -> Ordering = match dir {
-            Direction::North => |a, b| Ord::cmp(&a.y, &b.y),
-            Direction::West => |a, b| Ord::cmp(&a.x, &b.x),
-            Direction::East => |a, b| Ord::cmp(&b.x, &a.x),
-            Direction::South => |a, b| Ord::cmp(&b.y, &a.y),
+            Direction::Up => |a, b| Ord::cmp(&a.y, &b.y),
+            Direction::Left => |a, b| Ord::cmp(&a.x, &b.x),
+            Direction::Right => |a, b| Ord::cmp(&b.x, &a.x),
+            Direction::Down => |a, b| Ord::cmp(&b.y, &a.y),
         };
         for pos in self.round_rocks.iter().sorted_by(comp) {
             let mut cur = Point::new(pos.x, pos.y);
             let mut next;
             loop {
-                if (dir == Direction::North && cur.y == 0)
-                    || (dir == Direction::South && cur.y == self.height - 1)
-                    || (dir == Direction::West && cur.x == 0)
-                    || (dir == Direction::East && cur.x == self.width - 1)
+                if (dir == Direction::Up && cur.y == 0)
+                    || (dir == Direction::Down && cur.y == self.height - 1)
+                    || (dir == Direction::Left && cur.x == 0)
+                    || (dir == Direction::Right && cur.x == self.width - 1)
                 {
                     break;
                 }
                 next = cur;
                 next.x = match dir {
-                    Direction::West => next.x - 1,
-                    Direction::East => next.x + 1,
+                    Direction::Left => next.x - 1,
+                    Direction::Right => next.x + 1,
                     _ => next.x,
                 };
                 next.y = match dir {
-                    Direction::North => next.y - 1,
-                    Direction::South => next.y + 1,
+                    Direction::Up => next.y - 1,
+                    Direction::Down => next.y + 1,
                     _ => next.y,
                 };
                 if self.square_rocks.contains(&next) {
@@ -147,13 +161,67 @@ impl Platform {
         self.round_rocks = rolled;
     }
 
-    fn spin(&mut self, cycles: usize) {
-        for _ in 0..cycles {
-            self.roll(Direction::North);
-            self.roll(Direction::West);
-            self.roll(Direction::South);
-            self.roll(Direction::East);
+    fn spin_once(&mut self) {
+        self.roll(Direction::Up);
+        self.roll(Direction::Left);
+        self.roll(Direction::Down);
+        self.roll(Direction::Right);
+    }
+
+    fn spin(&mut self, cycles: usize, progress: &Progress) {
+        // The rocks settle into a loop long before a billion spins, so only
+        // simulate until it repeats. HashSets can't be hashed, hence the Vecs.
+        let start = self.round_rocks.iter().copied().sorted().collect_vec();
+        progress.message("spinning until it repeats");
+        let end = nth_state(
+            start,
+            |rocks| {
+                self.round_rocks = rocks.iter().copied().collect();
+                self.spin_once();
+                progress.inc(1);
+                self.round_rocks.iter().copied().sorted().collect_vec()
+            },
+            cycles,
+        );
+        self.round_rocks = end.into_iter().collect();
+    }
+
+    /// Shows the first few spin cycles, one roll at a time.
+    fn animate(&mut self, spins: usize, viz: &Visualizer) {
+        viz.show(|| {
+            self.frame()
+                .with_caption(format!("Load {}", self.get_load()))
+        });
+        for spin in 1..=spins {
+            for dir in [
+                Direction::Up,
+                Direction::Left,
+                Direction::Down,
+                Direction::Right,
+            ] {
+                self.roll(dir);
+                viz.show(|| {
+                    let caption = format!("Spin {spin}, rolled {dir:?}: load {}", self.get_load());
+                    self.frame().with_caption(caption)
+                });
+            }
+        }
+    }
+
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::filled(
+            self.width,
+            self.height,
+            Cell::colored('.', Color::BrightBlack),
+        );
+        let to_ipoint = |p: &Point| IPoint::new(p.x as isize, p.y as isize);
+        for p in &self.square_rocks {
+            frame.set(to_ipoint(p), Cell::colored('#', Color::Blue));
         }
+        for p in &self.round_rocks {
+            frame.set(to_ipoint(p), Cell::colored('O', Color::BrightYellow).bold());
+        }
+        frame
     }
 
     // ;)
@@ -168,9 +236,11 @@ impl Platform {
 
 #[cfg(test)]
 mod tests {
-    use anyhow::Ok;
+    use proptest::{collection::vec, prelude::*};
+    use std::{cell::RefCell, rc::Rc};
 
     use super::*;
+    use crate::common::differential;
 
     const EXAMPLE_INPUT: &str = "O....#....
 O.OO#....#
@@ -202,7 +272,7 @@ O.#..O.#.#
     #[test]
     fn should_roll() -> Result<()> {
         let mut test = ".#O.\nOOOO".parse::<Platform>()?;
-        test.roll(Direction::North);
+        test.roll(Direction::Up);
         assert_eq!(
             HashSet::from_iter(vec![
                 Point::new(0, 0),
@@ -219,29 +289,27 @@ O.#..O.#.#
     #[test]
     fn should_get_load() -> Result<()> {
         let mut test = ".#O.\nOOOO".parse::<Platform>()?;
-        test.roll(Direction::North);
+        test.roll(Direction::Up);
         assert_eq!(8, test.get_load());
         Ok(())
     }
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let mut test = EXAMPLE_INPUT.parse::<Platform>()?;
-        test.roll(Direction::North);
-        assert_eq!(136, test.get_load());
+        assert_eq!(136, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_roll_in_other_directions() -> Result<()> {
         let mut test = ".#O.\nOO.O".parse::<Platform>()?;
-        test.roll(Direction::South);
+        test.roll(Direction::Down);
         assert_eq!(4, test.get_load());
-        test.roll(Direction::North);
+        test.roll(Direction::Up);
         assert_eq!(7, test.get_load());
-        test.roll(Direction::East);
+        test.roll(Direction::Right);
         assert!(test.round_rocks.contains(&Point::new(3, 1)));
-        test.roll(Direction::West);
+        test.roll(Direction::Left);
         assert!(test.round_rocks.contains(&Point::new(0, 1)));
         Ok(())
     }
@@ -249,16 +317,47 @@ O.#..O.#.#
     #[test]
     fn should_cycle() -> Result<()> {
         let mut test = "...\n.O.\n...".parse::<Platform>()?;
-        test.spin(1);
+        test.spin(1, &Progress::hidden());
         assert_eq!(HashSet::from_iter(vec![Point::new(2, 2)]), test.round_rocks);
         Ok(())
     }
 
+    #[test]
+    fn should_animate() -> Result<()> {
+        let mut test = ".#.\n.O.\n...".parse::<Platform>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        test.animate(1, &Visualizer::new(frames.clone()));
+        let frames = frames.borrow();
+        assert_eq!(5, frames.len());
+        assert_eq!(vec![".#.", ".O.", "..."], frames[0].plain_lines());
+        assert_eq!(vec![".#.", "O..", "..."], frames[2].plain_lines());
+        assert_eq!(vec![".#.", "...", "..O"], frames[4].plain_lines());
+        assert_eq!("Spin 1, rolled Right: load 1", frames[4].caption());
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let mut test = EXAMPLE_INPUT.parse::<Platform>()?;
-        test.spin(MAX_CYCLES);
-        assert_eq!(64, test.get_load());
+        assert_eq!(64, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
+
+    #[test]
+    fn should_spin_differentially() {
+        let platform = (1..7usize).prop_flat_map(|width| {
+            let row = proptest::string::string_regex(&format!("[.#O]{{{width}}}")).unwrap();
+            vec(row, 1..7)
+        });
+        let case = (platform.prop_map(|rows| rows.join("\n")), 0..30usize);
+        differential::check("day14", case, |(input, cycles)| {
+            let mut literal = input.parse::<Platform>().unwrap();
+            for _ in 0..*cycles {
+                literal.spin_once();
+            }
+            let mut skipped = input.parse::<Platform>().unwrap();
+            skipped.spin(*cycles, &Progress::hidden());
+            prop_assert_eq!(literal.round_rocks, skipped.round_rocks);
+            Ok(())
+        });
+    }
 }
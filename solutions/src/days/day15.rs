@@ -1,10 +1,16 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{
+    input::{csv, csv_as},
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 15;
     const TITLE: &'static str = "Lens Library";
 
@@ -12,17 +18,22 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let sequence = self.input().get_csv()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let sequence = csv(input);
         Ok(hash_sequence(&sequence))
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let sequence = self.input().get_csv_as::<Instruction>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let sequence = csv_as::<Instruction>(input)?;
         let mut library = Library::new();
         library.run(&sequence);
         Ok(library.get_focus_power())
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        csv_as::<Instruction>(input)?;
+        Ok(())
+    }
 }
 
 fn hash(s: &str) -> usize {
@@ -137,7 +148,7 @@ impl FromStr for Instruction {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        for (i, c) in s.chars().enumerate() {
+        for (i, c) in s.char_indices() {
             match c {
                 '-' => {
                     return Ok(Instruction::Remove(s[0..i].to_owned()));
@@ -157,9 +168,8 @@ impl FromStr for Instruction {
 
 #[cfg(test)]
 mod tests {
-    use itertools::Itertools;
-
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
@@ -172,8 +182,7 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let inputs = EXAMPLE_INPUT.split(',').map(|s| s.to_owned()).collect_vec();
-        assert_eq!(1320, hash_sequence(&inputs));
+        assert_eq!(1320, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -228,14 +237,16 @@ mod tests {
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let mut library = Library::new();
-        let instructions = EXAMPLE_INPUT
-            .split(',')
-            .filter_map(|s| s.parse::<Instruction>().ok())
-            .collect_vec();
-        library.run(&instructions);
-        println!("{library:?}");
-        assert_eq!(145, library.get_focus_power());
+        assert_eq!(145, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
+
+    proptest! {
+        #[test]
+        fn should_hash_a_character_at_a_time(s in "[ -~]{0,20}", c in proptest::char::range(' ', '~')) {
+            let h = hash(&s);
+            prop_assert!(h < 256);
+            prop_assert_eq!((h + c as usize) * 17 % 256, hash(&format!("{s}{c}")));
+        }
+    }
 }
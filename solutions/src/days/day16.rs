@@ -3,11 +3,22 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::IPoint, solver::Solver};
+use crate::{
+    common::{
+        search::{bfs_distances, dfs_reach},
+        Direction, IPoint,
+    },
+    solver::Solver,
+    viz::{Cell, Frame, Visualizer},
+};
 use anyhow::{anyhow, Ok, Result};
+use colored::Color;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 16;
     const TITLE: &'static str = "The Floor Will Be Lava";
 
@@ -15,24 +26,27 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let contraption = self.input().get_as::<Contraption>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let contraption = input.parse::<Contraption>()?;
         let illuminated = contraption.illuminate(IPoint::new(0, 0), Direction::Right);
         Ok(illuminated.len())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let contraption = self.input().get_as::<Contraption>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let contraption = input.parse::<Contraption>()?;
         Ok(contraption.find_max_illumination())
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Contraption>()?;
+        Ok(())
+    }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        let contraption = input.parse::<Contraption>()?;
+        contraption.animate(IPoint::new(0, 0), Direction::Right, viz);
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -58,44 +72,29 @@ impl TryFrom<char> for Tile {
     }
 }
 impl Tile {
-    const OFFSET_UP: (isize, isize) = (0, -1);
-    const OFFSET_DOWN: (isize, isize) = (0, 1);
-    const OFFSET_LEFT: (isize, isize) = (-1, 0);
-    const OFFSET_RIGHT: (isize, isize) = (1, 0);
+    fn to_char(self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::MirrorUp => '/',
+            Tile::MirrorDown => '\\',
+            Tile::SplitVert => '|',
+            Tile::SplitHoriz => '-',
+        }
+    }
 
-    fn get_next_offset(&self, direction: Direction) -> Vec<((isize, isize), Direction)> {
-        let mut offsets = vec![];
+    fn get_next_directions(&self, direction: Direction) -> Vec<Direction> {
         match (self, direction) {
-            (Tile::Empty, Direction::Up) => offsets.push((Tile::OFFSET_UP, direction)),
-            (Tile::Empty, Direction::Down) => offsets.push((Tile::OFFSET_DOWN, direction)),
-            (Tile::Empty, Direction::Left) => offsets.push((Tile::OFFSET_LEFT, direction)),
-            (Tile::Empty, Direction::Right) => offsets.push((Tile::OFFSET_RIGHT, direction)),
-            (Tile::MirrorUp, Direction::Up) => offsets.push((Tile::OFFSET_RIGHT, Direction::Right)),
-            (Tile::MirrorUp, Direction::Down) => offsets.push((Tile::OFFSET_LEFT, Direction::Left)),
-            (Tile::MirrorUp, Direction::Left) => offsets.push((Tile::OFFSET_DOWN, Direction::Down)),
-            (Tile::MirrorUp, Direction::Right) => offsets.push((Tile::OFFSET_UP, Direction::Up)),
-            (Tile::MirrorDown, Direction::Up) => offsets.push((Tile::OFFSET_LEFT, Direction::Left)),
-            (Tile::MirrorDown, Direction::Down) => {
-                offsets.push((Tile::OFFSET_RIGHT, Direction::Right))
-            }
-            (Tile::MirrorDown, Direction::Left) => offsets.push((Tile::OFFSET_UP, Direction::Up)),
-            (Tile::MirrorDown, Direction::Right) => {
-                offsets.push((Tile::OFFSET_DOWN, Direction::Down))
-            }
-            (Tile::SplitVert, Direction::Up) => offsets.push((Tile::OFFSET_UP, direction)),
-            (Tile::SplitVert, Direction::Down) => offsets.push((Tile::OFFSET_DOWN, direction)),
-            (Tile::SplitVert, _) => offsets.extend([
-                (Tile::OFFSET_UP, Direction::Up),
-                (Tile::OFFSET_DOWN, Direction::Down),
-            ]),
-            (Tile::SplitHoriz, Direction::Left) => offsets.push((Tile::OFFSET_LEFT, direction)),
-            (Tile::SplitHoriz, Direction::Right) => offsets.push((Tile::OFFSET_RIGHT, direction)),
-            (Tile::SplitHoriz, _) => offsets.extend([
-                (Tile::OFFSET_LEFT, Direction::Left),
-                (Tile::OFFSET_RIGHT, Direction::Right),
-            ]),
+            (Tile::Empty, _) => vec![direction],
+            // '/' turns up/down beams right and left/right beams left; '\' the reverse
+            (Tile::MirrorUp, d) if d.is_horizontal() => vec![d.turn_left()],
+            (Tile::MirrorUp, d) => vec![d.turn_right()],
+            (Tile::MirrorDown, d) if d.is_horizontal() => vec![d.turn_right()],
+            (Tile::MirrorDown, d) => vec![d.turn_left()],
+            (Tile::SplitVert, Direction::Up | Direction::Down) => vec![direction],
+            (Tile::SplitVert, _) => vec![Direction::Up, Direction::Down],
+            (Tile::SplitHoriz, Direction::Left | Direction::Right) => vec![direction],
+            (Tile::SplitHoriz, _) => vec![Direction::Left, Direction::Right],
         }
-        offsets
     }
 }
 
@@ -127,33 +126,61 @@ impl FromStr for Contraption {
 }
 impl Contraption {
     fn illuminate(&self, start: IPoint, direction: Direction) -> HashSet<IPoint> {
-        let mut visited = HashSet::new();
-        let beam = self.get_next(start, direction, &mut visited);
-        HashSet::from_iter(beam)
-    }
-
-    fn get_next(
-        &self,
-        start: IPoint,
-        direction: Direction,
-        visited: &mut HashSet<(IPoint, Direction)>,
-    ) -> Vec<IPoint> {
-        let mut illuminated = vec![];
-        let tile;
-        match self.tiles.get(&start) {
-            Some(t) => tile = t,
-            None => return illuminated,
+        if !self.tiles.contains_key(&start) {
+            return HashSet::new();
         }
-        visited.insert((start, direction));
-        illuminated.push(start);
-        for (offset, next_dir) in tile.get_next_offset(direction) {
-            let next = start + offset;
-            if visited.contains(&(next, next_dir)) {
-                continue;
+        let beams = dfs_reach((start, direction), |beam| self.advance(beam));
+        beams.into_iter().map(|(pos, _)| pos).collect()
+    }
+
+    /// Where a beam goes next, splitting in two if it needs to.
+    fn advance(&self, &(pos, dir): &(IPoint, Direction)) -> Vec<(IPoint, Direction)> {
+        self.tiles[&pos]
+            .get_next_directions(dir)
+            .into_iter()
+            .map(move |next_dir| (pos + next_dir.offset(), next_dir))
+            .filter(|(next, _)| self.tiles.contains_key(next))
+            .collect()
+    }
+
+    /// Shows the beams spreading out one tile at a time.
+    fn animate(&self, start: IPoint, direction: Direction, viz: &Visualizer) {
+        if !self.tiles.contains_key(&start) {
+            return;
+        }
+        let distances = bfs_distances((start, direction), |beam| self.advance(beam));
+        let furthest = distances.values().copied().max().unwrap_or(0);
+        let mut energized = HashSet::new();
+        for step in 0..=furthest {
+            let heads = distances
+                .iter()
+                .filter(|&(_, &d)| d == step)
+                .map(|(&beam, _)| beam)
+                .collect::<Vec<_>>();
+            energized.extend(heads.iter().map(|&(pos, _)| pos));
+            viz.show(|| {
+                self.frame(&energized, &heads)
+                    .with_caption(format!("{} tiles energized", energized.len()))
+            });
+        }
+    }
+
+    fn frame(&self, energized: &HashSet<IPoint>, heads: &[(IPoint, Direction)]) -> Frame {
+        let mut frame = Frame::new(self.width, self.height);
+        for (&pos, &tile) in &self.tiles {
+            let cell = match (tile, energized.contains(&pos)) {
+                (Tile::Empty, true) => Cell::colored('#', Color::Yellow),
+                (_, true) => Cell::colored(tile.to_char(), Color::BrightYellow).bold(),
+                (_, false) => Cell::colored(tile.to_char(), Color::BrightBlack),
+            };
+            frame.set(pos, cell);
+        }
+        for &(pos, dir) in heads {
+            if self.tiles[&pos] == Tile::Empty {
+                frame.set(pos, Cell::colored(dir.arrow(), Color::BrightWhite).bold());
             }
-            illuminated.extend(self.get_next(next, next_dir, visited));
         }
-        illuminated
+        frame
     }
 
     fn find_max_illumination(&self) -> usize {
@@ -189,6 +216,7 @@ impl Contraption {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{cell::RefCell, rc::Rc};
 
     const EXAMPLE_INPUT: &str = r".|...\....
 |.-.\.....
@@ -218,6 +246,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_animate() -> Result<()> {
+        let test = ".\\/-.\n.-/..".parse::<Contraption>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        test.animate(
+            IPoint::new(0, 0),
+            Direction::Right,
+            &Visualizer::new(frames.clone()),
+        );
+        let frames = frames.borrow();
+        assert_eq!(vec![">\\/-.", ".-/.."], frames[0].plain_lines());
+        let last = frames.last().unwrap();
+        assert_eq!(vec!["#\\/->", "#-/.."], last.plain_lines());
+        assert_eq!("8 tiles energized", last.caption());
+        Ok(())
+    }
+
     #[test]
     fn should_illuminate() -> Result<()> {
         let test = ".\\/-.\n.-/..".parse::<Contraption>()?;
@@ -238,17 +283,13 @@ mod tests {
 
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let contraption = EXAMPLE_INPUT.parse::<Contraption>()?;
-        let beam = contraption.illuminate(IPoint::new(0, 0), Direction::Right);
-        assert_eq!(46, beam.len());
+        assert_eq!(46, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let contraption = EXAMPLE_INPUT.parse::<Contraption>()?;
-        let max = contraption.find_max_illumination();
-        assert_eq!(51, max);
+        assert_eq!(51, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
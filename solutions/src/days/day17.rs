@@ -1,16 +1,21 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap},
-    str::FromStr,
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    common::{
+        search::{dijkstra, Path},
+        Direction, IPoint,
+    },
+    solver::Solver,
+    viz::{Cell, Frame, Visualizer},
 };
-
-use crate::{common::IPoint, solver::Solver};
 use anyhow::{anyhow, Result};
-
-type Point = IPoint;
+use colored::Color;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 17;
     const TITLE: &'static str = "Clumsy Crucible";
 
@@ -18,46 +23,34 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let map = self.input().get_as::<CityMap>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<CityMap>()?;
         map.find_crucible_path()
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let map = self.input().get_as::<CityMap>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let map = input.parse::<CityMap>()?;
         map.find_ultra_crucible_path()
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Direction {
-    Unknown,
-    Down,
-    Right,
-    Left,
-    Up,
-}
-impl Direction {
-    fn opposite(&self) -> Self {
-        match self {
-            Direction::Down => Direction::Up,
-            Direction::Up => Direction::Down,
-            Direction::Right => Direction::Left,
-            Direction::Left => Direction::Right,
-            Direction::Unknown => Direction::Unknown,
-        }
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<CityMap>()?;
+        Ok(())
+    }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        let map = input.parse::<CityMap>()?;
+        map.animate("Crucible", &map.find_path(0, 3)?, viz);
+        map.animate("Ultra crucible", &map.find_path(4, 10)?, viz);
+        Ok(())
     }
 }
 
-const ADJACENTS: [((isize, isize), Direction); 4] = [
-    ((0, 1), Direction::Down),
-    ((1, 0), Direction::Right),
-    ((-1, 0), Direction::Left),
-    ((0, -1), Direction::Up),
-];
+/// Where the crucible is, which way it's heading and how long it's been going that way.
+type Crucible = (IPoint, Option<Direction>, usize);
 
 struct CityMap {
-    blocks: HashMap<Point, u8>,
+    blocks: HashMap<IPoint, u8>,
     max_x: isize,
     max_y: isize,
 }
@@ -71,7 +64,7 @@ impl FromStr for CityMap {
             max_y = y as isize;
             for (x, c) in line.chars().enumerate() {
                 max_x = x as isize;
-                blocks.insert(Point::new(x as isize, y as isize), (c as u8) % 48);
+                blocks.insert(IPoint::new(x as isize, y as isize), (c as u8) % 48);
             }
         }
         Ok(CityMap {
@@ -83,82 +76,82 @@ impl FromStr for CityMap {
 }
 impl CityMap {
     fn find_crucible_path(&self) -> Result<usize> {
-        self.find_path(
-            Point::new(0, 0),
-            Point::new(self.max_x, self.max_y),
-            Direction::Unknown,
-            0,
-            3,
-        )
+        Ok(self.find_path(0, 3)?.cost)
     }
 
     fn find_ultra_crucible_path(&self) -> Result<usize> {
-        self.find_path(
-            Point::new(0, 0),
-            Point::new(self.max_x, self.max_y),
-            Direction::Unknown,
-            4,
-            10,
-        )
+        Ok(self.find_path(4, 10)?.cost)
     }
 
-    fn find_path(
-        &self,
-        start: Point,
-        end: Point,
-        dir: Direction,
-        min_steps: usize,
-        max_steps: usize,
-    ) -> Result<usize> {
-        let mut frontier = BinaryHeap::from([Reverse((0, 0, dir, start))]);
-        let mut visited: HashMap<(Point, Direction, usize), usize> = HashMap::new();
+    /// The least lossy route from the top left to the bottom right.
+    fn find_path(&self, min_steps: usize, max_steps: usize) -> Result<Path<Crucible>> {
+        let end = IPoint::new(self.max_x, self.max_y);
+        let path = dijkstra(
+            (IPoint::new(0, 0), None, 0),
+            |&(pos, dir, steps)| self.get_next_nodes(pos, dir, steps, min_steps, max_steps),
+            |&(pos, _, steps)| pos == end && steps >= min_steps,
+        );
+        path.ok_or(anyhow!("No valid path found!"))
+    }
 
-        while let Some(Reverse((cur_loss, cur_steps, cur_dir, cur_pos))) = frontier.pop() {
-            if cur_pos == end && cur_steps >= min_steps {
-                return Ok(cur_loss);
-            }
-            for (next, next_loss, next_dir, next_steps) in
-                self.get_next_nodes(cur_pos, cur_loss, cur_dir, cur_steps, min_steps, max_steps)
-            {
-                if !visited.contains_key(&(next, next_dir, next_steps)) {
-                    frontier.push(Reverse((next_loss, next_steps, next_dir, next)));
-                    visited.insert((next, next_dir, next_steps), next_loss);
-                }
+    /// Shows the crucible making its way along the route.
+    fn animate(&self, name: &str, path: &Path<Crucible>, viz: &Visualizer) {
+        let (width, height) = (self.max_x as usize + 1, self.max_y as usize + 1);
+        let mut frame = Frame::new(width, height);
+        for (&pos, &loss) in &self.blocks {
+            let color = match loss {
+                0..=3 => Color::BrightBlack,
+                4..=6 => Color::Red,
+                _ => Color::BrightRed,
+            };
+            frame.set(pos, Cell::colored((b'0' + loss) as char, color));
+        }
+        let mut lost = 0;
+        for &(pos, dir, _) in &path.states {
+            if let Some(dir) = dir {
+                lost += self.blocks[&pos] as usize;
+                frame.set(pos, Cell::colored(dir.arrow(), Color::BrightWhite).bold());
             }
+            viz.show(|| {
+                frame
+                    .clone()
+                    .with_caption(format!("{name}: {lost} heat lost"))
+            });
         }
-        Err(anyhow!("No valid path found!"))
     }
 
     fn get_next_nodes(
         &self,
-        pos: Point,
-        loss: usize,
-        dir: Direction,
+        pos: IPoint,
+        dir: Option<Direction>,
         steps: usize,
         min_steps: usize,
         max_steps: usize,
-    ) -> Vec<(Point, usize, Direction, usize)> {
+    ) -> Vec<(Crucible, usize)> {
         let mut ret = vec![];
-        for (offset, next_direction) in ADJACENTS {
-            if next_direction == dir.opposite() {
-                continue;
-            }
-            if next_direction == dir && steps == max_steps {
-                continue;
-            }
-            if dir != Direction::Unknown && next_direction != dir && steps < min_steps {
-                continue;
+        for next_direction in Direction::ALL {
+            // Until we've moved, any direction goes
+            if let Some(cur_direction) = dir {
+                if next_direction == cur_direction.opposite() {
+                    continue;
+                }
+                if next_direction == cur_direction && steps == max_steps {
+                    continue;
+                }
+                if next_direction != cur_direction && steps < min_steps {
+                    continue;
+                }
             }
-            let next = pos + offset;
+            let next = pos + next_direction.offset();
             let next_loss = match self.blocks.get(&next) {
                 Some(&x) => x as usize,
                 None => continue,
             };
-            let next_steps = match dir == next_direction {
+            let next_steps = match dir == Some(next_direction) {
                 true => steps + 1,
                 false => 1,
             };
-            ret.push((next, loss + next_loss, next_direction, next_steps));
+            ret.push(((next, Some(next_direction), next_steps), next_loss));
         }
         ret
     }
@@ -167,6 +160,7 @@ impl CityMap {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{cell::RefCell, rc::Rc};
 
     const EXAMPLE_INPUT: &str = "2413432311323
 3215453535623
@@ -186,10 +180,10 @@ mod tests {
     fn should_parse() -> Result<()> {
         let test = "12\n34".parse::<CityMap>()?;
         let expected = HashMap::from_iter(vec![
-            (Point::new(0, 0), 1),
-            (Point::new(1, 0), 2),
-            (Point::new(0, 1), 3),
-            (Point::new(1, 1), 4),
+            (IPoint::new(0, 0), 1),
+            (IPoint::new(1, 0), 2),
+            (IPoint::new(0, 1), 3),
+            (IPoint::new(1, 1), 4),
         ]);
         assert_eq!(expected, test.blocks);
         Ok(())
@@ -203,19 +197,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_animate() -> Result<()> {
+        let test = "14999\n23111\n99991".parse::<CityMap>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        test.animate(
+            "Test",
+            &test.find_path(0, 3)?,
+            &Visualizer::new(frames.clone()),
+        );
+        let frames = frames.borrow();
+        assert_eq!(vec!["14999", "23111", "99991"], frames[0].plain_lines());
+        let last = frames.last().unwrap();
+        assert_eq!(vec!["1>999", "2v>>>", "9999v"], last.plain_lines());
+        assert_eq!("Test: 11 heat lost", last.caption());
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let city = EXAMPLE_INPUT.parse::<CityMap>()?;
-        let heat_loss = city.find_crucible_path()?;
-        assert_eq!(102, heat_loss);
+        assert_eq!(102, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let city = EXAMPLE_INPUT.parse::<CityMap>()?;
-        let heat_loss = city.find_ultra_crucible_path()?;
-        assert_eq!(94, heat_loss);
+        assert_eq!(94, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
@@ -1,16 +1,19 @@
 use std::str::FromStr;
 
 use crate::{
-    common::{Direction, IPoint},
+    common::{polygon::Polygon, search::dfs_reach, Direction, IPoint},
     solver::Solver,
+    viz::{Cell, Frame, Visualizer},
 };
 use anyhow::{anyhow, Result};
+use colored::Color;
 use itertools::Itertools;
 
-type Point = IPoint;
-
 pub struct Solution;
-impl Solver<isize, isize> for Solution {
+impl Solver for Solution {
+    type PartOne = isize;
+    type PartTwo = isize;
+
     const DAY: u8 = 18;
     const TITLE: &'static str = "Lavaduct Lagoon";
 
@@ -18,21 +21,34 @@ impl Solver<isize, isize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<isize> {
-        let plan = self.input().get_as::<DigPlan>()?;
+    fn part_one(&self, input: &str) -> Result<isize> {
+        let plan = input.parse::<DigPlan>()?;
         Ok(plan.get_volume())
     }
 
-    fn part_two(&self) -> Result<isize> {
-        let plan = DigPlan::from_bad_str(&self.input().get().unwrap())?;
+    fn part_two(&self, input: &str) -> Result<isize> {
+        let plan = DigPlan::from_bad_str(input)?;
         Ok(plan.get_volume())
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<DigPlan>()?;
+        DigPlan::from_bad_str(input)?;
+        Ok(())
+    }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        // Only the first plan is small enough to draw
+        let plan = input.parse::<DigPlan>()?;
+        plan.animate(viz);
+        Ok(())
+    }
 }
 
 struct Instruction {
     direction: Direction,
     meters: isize,
-    #[allow(dead_code)] // keeping for posterity
+    /// Only used to paint the trench when visualizing.
     color: (u8, u8, u8),
 }
 impl FromStr for Instruction {
@@ -45,9 +61,12 @@ impl FromStr for Instruction {
         }
         let direction = parts[0].parse()?;
         let meters = parts[1].parse()?;
-        let r = u8::from_str_radix(&parts[2][2..4], 16)?;
-        let g = u8::from_str_radix(&parts[2][4..6], 16)?;
-        let b = u8::from_str_radix(&parts[2][6..8], 16)?;
+        let color = parts[2]
+            .strip_prefix("(#")
+            .and_then(|c| c.strip_suffix(')'))
+            .filter(|c| is_hex(c, 6))
+            .ok_or(anyhow!("Invalid colour: {s}"))?;
+        let [_, r, g, b] = u32::from_str_radix(color, 16)?.to_be_bytes();
 
         Ok(Instruction {
             direction,
@@ -56,27 +75,18 @@ impl FromStr for Instruction {
         })
     }
 }
-impl FromStr for Direction {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        match s {
-            "U" => Ok(Direction::Up),
-            "D" => Ok(Direction::Down),
-            "L" => Ok(Direction::Left),
-            "R" => Ok(Direction::Right),
-            _ => Err(anyhow!("Invalid direction: {s}")),
-        }
-    }
-}
 impl Instruction {
     fn from_bad_str(s: &str) -> Result<Self> {
         let i = match s.find('#') {
             Some(idx) => idx,
             None => return Err(anyhow!("Invalid instruction: {s}")),
         };
-        let meters = isize::from_str_radix(&s[i + 1..i + 6], 16)?;
-        let direction = match &s[i + 6..i + 7] {
+        let code = s[i + 1..]
+            .get(..6)
+            .filter(|c| is_hex(c, 6))
+            .ok_or(anyhow!("Invalid instruction: {s}"))?;
+        let meters = isize::from_str_radix(&code[..5], 16)?;
+        let direction = match &code[5..] {
             "0" => Direction::Right,
             "1" => Direction::Down,
             "2" => Direction::Left,
@@ -91,6 +101,11 @@ impl Instruction {
     }
 }
 
+/// Exactly `len` hex digits, and nothing else (not even a sign).
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 struct DigPlan {
     instructions: Vec<Instruction>,
 }
@@ -114,40 +129,88 @@ impl DigPlan {
         Ok(DigPlan { instructions })
     }
 
-    fn dig(&self) -> Vec<Point> {
+    fn dig(&self) -> Vec<IPoint> {
         let mut vertices = vec![];
-        let mut cur = Point::new(0, 0);
+        let mut cur = IPoint::new(0, 0);
         for ins in &self.instructions {
-            cur = match ins.direction {
-                Direction::Up => cur + (0, -ins.meters),
-                Direction::Down => cur + (0, ins.meters),
-                Direction::Left => cur + (-ins.meters, 0),
-                Direction::Right => cur + (ins.meters, 0),
-            };
+            cur += ins.direction.offset() * ins.meters;
             vertices.push(cur);
         }
         vertices
     }
 
     fn get_volume(&self) -> isize {
+        // The trench is a whole metre wide, so count every grid point on or
+        // inside the outline rather than the area it encloses
+        Polygon::new(self.dig()).lattice_points() as isize
+    }
+
+    /// Shows the trench being dug out in its own colours, then the lagoon
+    /// inside it.
+    fn animate(&self, viz: &Visualizer) {
         let vertices = self.dig();
-        let mut area = 0;
-        let mut perim = 0;
-        for i in 0..vertices.len() {
-            let j = (i + 1) % vertices.len();
-            area += vertices[i].x * vertices[j].y;
-            area -= vertices[i].y * vertices[j].x;
-            perim += (vertices[i].x - vertices[j].x).abs() + (vertices[i].y - vertices[j].y).abs();
+        let (min_x, max_x) = vertices
+            .iter()
+            .map(|v| v.x)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (min_y, max_y) = vertices
+            .iter()
+            .map(|v| v.y)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        // Leave a border so the outside can be flooded all the way round
+        let offset = IPoint::new(1 - min_x.min(0), 1 - min_y.min(0));
+        let (width, height) = (max_x.max(0) + offset.x + 2, max_y.max(0) + offset.y + 2);
+        let mut frame = Frame::filled(width as usize, height as usize, Cell::plain(' '));
+
+        let mut cur = offset;
+        let mut dug = 0;
+        let stride = (self.instructions.len() / 200).max(1);
+        for (i, ins) in self.instructions.iter().enumerate() {
+            let (r, g, b) = ins.color;
+            for _ in 0..ins.meters {
+                cur += ins.direction.offset();
+                frame.set(cur, Cell::colored('#', Color::TrueColor { r, g, b }));
+            }
+            dug += ins.meters;
+            if (i + 1) % stride == 0 || i + 1 == self.instructions.len() {
+                viz.show(|| {
+                    frame
+                        .clone()
+                        .with_caption(format!("{dug} metres of trench"))
+                });
+            }
+        }
+
+        if !viz.is_enabled() {
+            return;
+        }
+        let outside = dfs_reach(IPoint::new(0, 0), |&p| {
+            Direction::ALL
+                .iter()
+                .map(move |d| p + d.offset())
+                .filter(|&n| frame.get(n).is_some_and(|c| c.ch == ' '))
+                .collect_vec()
+        });
+        let lagoon = (0..height)
+            .flat_map(|y| (0..width).map(move |x| IPoint::new(x, y)))
+            .filter(|p| frame.get(*p).is_some_and(|c| c.ch == ' ') && !outside.contains(p))
+            .collect_vec();
+        for p in lagoon {
+            frame.set(p, Cell::colored('~', Color::Blue));
         }
-        perim = (perim / 2) + 1;
-        area = (area / 2).abs();
-        area + perim
+        let volume = self.get_volume();
+        viz.show(|| frame.with_caption(format!("{volume} cubic metres of lava")));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{cell::RefCell, rc::Rc};
 
     const EXAMPLE_INPUT: &str = "R 6 (#70c710)
 D 5 (#0dc571)
@@ -181,10 +244,43 @@ U 2 (#7a21e3)";
         Ok(())
     }
 
+    #[test]
+    fn should_survive_a_flat_plan() -> Result<()> {
+        // Digging straight out and back encloses nothing, which used to
+        // underflow
+        let test = "R 2 (#000000)\nL 2 (#000000)".parse::<DigPlan>()?;
+        assert_eq!(0, Polygon::new(test.dig()).interior_points());
+        Ok(())
+    }
+
+    #[test]
+    fn should_animate() -> Result<()> {
+        let test =
+            "R 2 (#ff0000)\nD 2 (#00ff00)\nL 2 (#0000ff)\nU 2 (#ffffff)".parse::<DigPlan>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        test.animate(&Visualizer::new(frames.clone()));
+        let frames = frames.borrow();
+        assert_eq!(5, frames.len());
+        assert_eq!(
+            vec!["     ", "  ## ", "     ", "     ", "     "][..],
+            frames[0].plain_lines()[..]
+        );
+        let last = frames.last().unwrap();
+        assert_eq!(
+            vec!["     ", " ### ", " #~# ", " ### ", "     "],
+            last.plain_lines()
+        );
+        assert_eq!(
+            Some(Color::TrueColor { r: 255, g: 0, b: 0 }),
+            last.get(IPoint::new(2, 1)).unwrap().color
+        );
+        assert_eq!("9 cubic metres of lava", last.caption());
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let plan = EXAMPLE_INPUT.parse::<DigPlan>()?;
-        assert_eq!(62, plan.get_volume());
+        assert_eq!(62, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
@@ -198,8 +294,7 @@ U 2 (#7a21e3)";
 
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let plan = DigPlan::from_bad_str(EXAMPLE_INPUT)?;
-        assert_eq!(952408144115, plan.get_volume());
+        assert_eq!(952408144115, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
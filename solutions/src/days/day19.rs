@@ -1,11 +1,17 @@
 use std::{collections::HashMap, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{
+    common::interval::{Hyperrectangle, Interval},
+    solver::Solver,
+};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 19;
     const TITLE: &'static str = "Aplenty";
 
@@ -13,15 +19,20 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let system = self.input().get_as::<System>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let system = input.parse::<System>()?;
         Ok(system.get_total_rating())
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let system = self.input().get_as::<System>()?;
+    fn part_two(&self, input: &str) -> Result<usize> {
+        let system = input.parse::<System>()?;
         Ok(system.get_possible_combinations())
     }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<System>()?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -36,7 +47,11 @@ impl FromStr for Part {
 
     fn from_str(input: &str) -> Result<Self> {
         let (mut x, mut m, mut a, mut s) = (0, 0, 0, 0);
-        for rating in input[1..input.len() - 1].split(',') {
+        let ratings = input
+            .strip_prefix('{')
+            .and_then(|r| r.strip_suffix('}'))
+            .ok_or(anyhow!("Invalid part: {input}"))?;
+        for rating in ratings.split(',') {
             if let Some((stat, value)) = rating.split('=').collect_tuple() {
                 let v = value.parse()?;
                 match stat {
@@ -64,6 +79,16 @@ enum Rating {
     A,
     S,
 }
+impl Rating {
+    fn axis(&self) -> usize {
+        match self {
+            Rating::X => 0,
+            Rating::M => 1,
+            Rating::A => 2,
+            Rating::S => 3,
+        }
+    }
+}
 impl FromStr for Rating {
     type Err = anyhow::Error;
 
@@ -122,15 +147,15 @@ impl FromStr for Operation {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s.find(':') {
-            Some(i) => {
-                if i < 3 {
-                    return Err(anyhow!("Invalid operation: {s}"));
-                }
-                let rating = s[0..1].parse::<Rating>()?;
-                let operator = s[1..2].parse::<Operator>()?;
-                let value = s[2..i].parse::<usize>()?;
-                let destination = s[i + 1..].parse::<Destination>()?;
+        match s.split_once(':') {
+            Some((condition, destination)) => {
+                let invalid = || anyhow!("Invalid operation: {s}");
+                let (rating, rest) = condition.split_at_checked(1).ok_or_else(invalid)?;
+                let (operator, value) = rest.split_at_checked(1).ok_or_else(invalid)?;
+                let rating = rating.parse::<Rating>()?;
+                let operator = operator.parse::<Operator>()?;
+                let value = value.parse::<usize>()?;
+                let destination = destination.parse::<Destination>()?;
                 Ok(Operation::Compare(rating, operator, value, destination))
             }
             None => Ok(Operation::Send(s.parse()?)),
@@ -151,14 +176,30 @@ impl FromStr for Workflow {
             None => return Err(anyhow!("Invalid workflow: {s}")),
         };
         let key = s[0..bracket].to_owned();
+        let body = s[bracket + 1..]
+            .strip_suffix('}')
+            .ok_or(anyhow!("Invalid workflow: {s}"))?;
         let mut operations = vec![];
-        for op in s[bracket + 1..s.len() - 1].split(',') {
+        for op in body.split(',') {
             operations.push(op.parse()?);
         }
+        // Otherwise a part could fall off the end without going anywhere
+        if !matches!(operations.last(), Some(Operation::Send(_))) {
+            return Err(anyhow!("Workflow doesn't end by sending parts on: {s}"));
+        }
         Ok(Workflow { key, operations })
     }
 }
 impl Workflow {
+    /// The other workflows this one can send parts to.
+    fn next_workflows(&self) -> impl Iterator<Item = &String> {
+        self.operations.iter().filter_map(|op| match op {
+            Operation::Compare(_, _, _, Destination::Workflow(key))
+            | Operation::Send(Destination::Workflow(key)) => Some(key),
+            _ => None,
+        })
+    }
+
     fn run(&self, part: &Part) -> Destination {
         for op in &self.operations {
             match op {
@@ -203,6 +244,7 @@ impl FromStr for System {
             let w = line.parse::<Workflow>()?;
             workflows.insert(w.key.clone(), w);
         }
+        check_workflows(&workflows)?;
         let parts = halves[1]
             .lines()
             .filter_map(|l| l.parse().ok())
@@ -214,6 +256,7 @@ impl FromStr for System {
         })
     }
 }
+
 impl System {
     fn run(&self) -> Vec<&Part> {
         let mut accepted = vec![];
@@ -243,50 +286,36 @@ impl System {
         ranges: PartRanges,
         op: &Operation,
     ) -> (Vec<PartRanges>, Option<PartRanges>) {
-        let mut accepted = vec![];
-        let mut next_range = ranges;
-        let mut remainder: Option<PartRanges> = None;
-        let next = match op {
+        let (next_range, remainder, next) = match op {
             Operation::Compare(rating, cmp, amt, dest) => {
-                let mut rem = ranges;
-                let (nr, rr) = match rating {
-                    Rating::X => (&mut next_range.x, &mut rem.x),
-                    Rating::M => (&mut next_range.m, &mut rem.m),
-                    Rating::A => (&mut next_range.a, &mut rem.a),
-                    Rating::S => (&mut next_range.s, &mut rem.s),
-                };
-                match cmp {
-                    Operator::LT => {
-                        if nr.max > *amt {
-                            nr.max = *amt - 1;
-                            rr.min = *amt;
-                        }
-                    }
+                let axis = rating.axis();
+                let (matched, rem) = match cmp {
+                    Operator::LT => ranges.split(axis, *amt),
                     Operator::GT => {
-                        if nr.min < *amt {
-                            nr.min = *amt + 1;
-                            rr.max = *amt;
-                        }
+                        let (below, above) = ranges.split(axis, amt + 1);
+                        (above, below)
                     }
-                }
-                remainder = Some(rem);
-                dest
+                };
+                (matched, rem, dest)
             }
-            Operation::Send(dest) => dest,
+            Operation::Send(dest) => (Some(ranges), None, dest),
+        };
+        let mut accepted = vec![];
+        let Some(next_range) = next_range else {
+            return (accepted, remainder);
         };
         match next {
             Destination::Accepted => accepted.push(next_range),
             Destination::Rejected => (),
             Destination::Workflow(key) => {
-                let workflow = self.workflows.get(key).unwrap();
-                let mut inner_range = next_range;
+                let workflow = &self.workflows[key];
+                let mut inner_range = Some(next_range);
                 for wf_op in &workflow.operations {
-                    let (acc, rem) = self.get_accepted_ranges(inner_range, wf_op);
+                    let Some(range) = inner_range else { break };
+                    let (acc, rem) = self.get_accepted_ranges(range, wf_op);
                     accepted.extend(acc);
                     // Cary forward the remainder
-                    if let Some(r) = rem {
-                        inner_range = r;
-                    }
+                    inner_range = rem;
                 }
             }
         }
@@ -295,50 +324,60 @@ impl System {
 
     fn get_possible_combinations(&self) -> usize {
         let (ranges, _) = self.get_accepted_ranges(
-            PartRanges::new(),
+            PartRanges::new([RATING_RANGE; 4]),
             &Operation::Send(Destination::Workflow(self.start.clone())),
         );
-        ranges.iter().map(|r| r.combinations()).sum()
+        ranges.iter().map(|r| r.volume()).sum()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct PartRanges {
-    x: Range,
-    m: Range,
-    a: Range,
-    s: Range,
-}
-impl PartRanges {
-    fn new() -> Self {
-        Self {
-            x: Range::new(),
-            m: Range::new(),
-            a: Range::new(),
-            s: Range::new(),
-        }
+/// Makes sure every part gets from the start to being accepted or rejected:
+/// there's a workflow to start at, every workflow sent to exists, and none
+/// of them send parts round in a circle.
+fn check_workflows(workflows: &HashMap<String, Workflow>) -> Result<()> {
+    if !workflows.contains_key(START_RULE) {
+        return Err(anyhow!("No {START_RULE} workflow to start from"));
     }
-
-    fn combinations(&self) -> usize {
-        self.x.count() * self.m.count() * self.a.count() * self.s.count()
+    let mut incoming: HashMap<&str, usize> = workflows.keys().map(|k| (k.as_str(), 0)).collect();
+    for workflow in workflows.values() {
+        for next in workflow.next_workflows() {
+            let count = incoming.get_mut(next.as_str()).ok_or(anyhow!(
+                "{} sends parts to {next}, which doesn't exist",
+                workflow.key
+            ))?;
+            *count += 1;
+        }
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Range {
-    min: usize,
-    max: usize,
-}
-impl Range {
-    fn new() -> Self {
-        Range { min: 1, max: 4000 }
+    // Peel off workflows nothing sends to until there are none left, which
+    // there won't be if some of them go round in a circle
+    let mut ready = incoming
+        .iter()
+        .filter(|(_, &n)| n == 0)
+        .map(|(&k, _)| k)
+        .collect_vec();
+    let mut peeled = 0;
+    while let Some(key) = ready.pop() {
+        peeled += 1;
+        for next in workflows[key].next_workflows() {
+            let count = incoming.get_mut(next.as_str()).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(next);
+            }
+        }
     }
-
-    fn count(&self) -> usize {
-        self.max - self.min + 1
+    match peeled == workflows.len() {
+        true => Ok(()),
+        false => Err(anyhow!("Workflows send parts round in a circle")),
     }
 }
 
+type PartRanges = Hyperrectangle<usize, 4>;
+const RATING_RANGE: Interval<usize> = Interval {
+    start: 1,
+    end: 4001,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,20 +437,27 @@ hdj{m>838:A,pv}
         Ok(())
     }
 
+    #[test]
+    fn should_reject_broken_workflows() {
+        let solution = Solution::new();
+        assert!(solution
+            .parse_input("in{x<5:A,R}\n\n{x=1,m=2,a=3,s=4}")
+            .is_ok());
+        assert!(solution.parse_input("px{A}\n\n").is_err());
+        assert!(solution.parse_input("in{x<5:px,R}\n\n").is_err());
+        assert!(solution.parse_input("in{px}\npx{m>1:in,A}\n\n").is_err());
+    }
+
     #[test]
     fn should_solve_part1() -> Result<()> {
-        let system = EXAMPLE_INPUT.parse::<System>()?;
-        let rating = system.get_total_rating();
-        assert_eq!(19114, rating);
+        assert_eq!(19114, Solution::new().part_one(EXAMPLE_INPUT)?);
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
         // Yeah, this was just a little too complicated to come up with an intermediate test
-        let system = EXAMPLE_INPUT.parse::<System>()?;
-        let combinations = system.get_possible_combinations();
-        assert_eq!(167409079868000, combinations);
+        assert_eq!(167409079868000, Solution::new().part_two(EXAMPLE_INPUT)?);
         Ok(())
     }
 }
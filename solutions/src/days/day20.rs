@@ -3,12 +3,15 @@ use std::{
     str::FromStr,
 };
 
-use crate::{common::lcm, solver::Solver};
+use crate::{common::num::lcm_all, progress::Progress, solver::Solver};
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 20;
     const TITLE: &'static str = "Pulse Propagation";
 
@@ -16,15 +19,24 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let mut bus = self.input().get_as::<Bus>()?;
+    fn part_one(&self, input: &str) -> Result<usize> {
+        let mut bus = input.parse::<Bus>()?;
         let (low, high) = bus.push_cycle(1000);
         Ok(low * high)
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let mut bus = self.input().get_as::<Bus>()?;
-        Ok(bus.find_least_to_sand())
+    fn part_two(&self, input: &str) -> Result<usize> {
+        self.part_two_with_progress(input, &Progress::hidden())
+    }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Bus>()?;
+        Ok(())
+    }
+
+    fn part_two_with_progress(&self, input: &str, progress: &Progress) -> Result<usize> {
+        let mut bus = input.parse::<Bus>()?;
+        bus.find_least_to_sand(progress)
     }
 }
 
@@ -116,16 +128,20 @@ impl FromStr for Bus {
                 return Err(anyhow!("Invalid wiring bus specification"));
             }
             let id;
-            let outputs = parts[1]
-                .split(", ")
-                .filter_map(|s| to_id(s).ok())
-                .collect_vec();
+            // Pulses sent nowhere would have nowhere to go
+            if parts[1].is_empty() {
+                return Err(anyhow!("Module isn't connected to anything: {l}"));
+            }
+            let outputs: Vec<u16> = parts[1].split(", ").map(to_id).try_collect()?;
             if parts[0] == "broadcaster" {
                 id = 0;
                 modules.insert(id, Box::new(Broadcast {}));
             } else {
-                id = to_id(&parts[0][1..]).unwrap();
-                match &parts[0][0..1] {
+                let (kind, name) = parts[0]
+                    .split_at_checked(1)
+                    .ok_or(anyhow!("Invalid module: {l}"))?;
+                id = to_id(name)?;
+                match kind {
                     "%" => {
                         modules.insert(id, Box::new(FlipFlop::new()));
                     }
@@ -133,7 +149,7 @@ impl FromStr for Bus {
                         let conj = Conjunction::new();
                         conjunctions.push((id, conj));
                     }
-                    _ => (),
+                    _ => return Err(anyhow!("Invalid module: {l}")),
                 }
             }
             if outputs.contains(&SAND_SHIFTER_ID) {
@@ -193,7 +209,7 @@ impl Bus {
             let Some(output) = module.pulse(pulse, &from) else {
                 continue;
             };
-            for &next in self.cables.get(&to).unwrap() {
+            for &next in &self.cables[&to] {
                 queue.push_back((to, next, output));
             }
         }
@@ -210,19 +226,26 @@ impl Bus {
         (total_low, total_high)
     }
 
-    fn find_least_to_sand(&mut self) -> usize {
+    fn find_least_to_sand(&mut self, progress: &Progress) -> Result<usize> {
+        progress.set_total(self.watched.len());
+        let mut found = 0;
         loop {
             self.push_button();
-            if self.watched.values().all(|v| v.is_some()) {
+            progress.message_with(|| format!("{} presses", self.button_presses));
+            let now_found = self.watched.values().filter(|v| v.is_some()).count();
+            progress.inc(now_found - found);
+            found = now_found;
+            if found == self.watched.len() {
                 break;
             }
         }
-        lcm(&self.watched.values().filter_map(|&v| v).collect_vec())
+        lcm_all(&self.watched.values().filter_map(|&v| v).collect_vec())
+            .ok_or(anyhow!("Button press count overflowed"))
     }
 }
 
 fn to_id(s: &str) -> Result<u16> {
-    let id = u16::from_str_radix(s, 36)?;
+    let id = u16::from_str_radix(s, 36).map_err(|_| anyhow!("Invalid module name: {s}"))?;
     Ok(id)
 }
 
@@ -255,6 +278,16 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_reject_broken_wiring() {
+        let solution = Solution::new();
+        assert!(solution.parse_input("broadcaster -> a\n%a -> ").is_err());
+        assert!(solution
+            .parse_input("broadcaster -> a\n%a -> b, ?")
+            .is_err());
+        assert!(solution.parse_input("broadcaster -> a\n*a -> b").is_err());
+    }
+
     #[test]
     fn should_pulse() -> Result<()> {
         let mut test = "broadcaster -> a, b\n%a -> b\n&b -> a".parse::<Bus>()?;
@@ -265,7 +298,7 @@ mod tests {
     }
 
     #[test]
-    fn should_solve_part1() -> Result<()> {
+    fn should_push_button() -> Result<()> {
         let mut bus = EXAMPLE_INPUT_PART_1.parse::<Bus>()?;
         let (low, high) = bus.push_button();
         assert_eq!(8, low);
@@ -273,11 +306,15 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_solve_part1() -> Result<()> {
+        assert_eq!(32000000, Solution::new().part_one(EXAMPLE_INPUT_PART_1)?);
+        Ok(())
+    }
+
     #[test]
     fn should_solve_part2() -> Result<()> {
-        let mut bus = EXAMPLE_INPUT_PART_2.parse::<Bus>()?;
-        let min = bus.find_least_to_sand();
-        assert_eq!(1, min);
+        assert_eq!(1, Solution::new().part_two(EXAMPLE_INPUT_PART_2)?);
         Ok(())
     }
 }
@@ -1,15 +1,24 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
-
-use crate::{common::IPoint, solver::Solver};
-use anyhow::Result;
+use std::{collections::HashSet, str::FromStr};
 
-type Point = IPoint;
+use crate::{
+    common::{
+        poly::{degree, extrapolate},
+        search::bfs_distances_within,
+        IPoint,
+    },
+    progress::Progress,
+    solver::Solver,
+    viz::{Cell, Frame, Visualizer},
+};
+use anyhow::{anyhow, Result};
+use colored::Color;
+use itertools::Itertools;
 
 pub struct Solution;
-impl Solver<usize, usize> for Solution {
+impl Solver for Solution {
+    type PartOne = usize;
+    type PartTwo = usize;
+
     const DAY: u8 = 21;
     const TITLE: &'static str = "Step Counter";
 
@@ -17,27 +26,42 @@ impl Solver<usize, usize> for Solution {
         Solution {}
     }
 
-    fn part_one(&self) -> Result<usize> {
-        let garden = self.input().get_as::<Garden>()?;
-        Ok(garden.walk(64))
+    fn part_one(&self, input: &str) -> Result<usize> {
+        self.part_one_with_progress(input, &Progress::hidden())
+    }
+
+    fn part_two(&self, input: &str) -> Result<usize> {
+        self.part_two_with_progress(input, &Progress::hidden())
+    }
+
+    fn parse_input(&self, input: &str) -> Result<()> {
+        input.parse::<Garden>()?;
+        Ok(())
+    }
+
+    fn part_one_with_progress(&self, input: &str, progress: &Progress) -> Result<usize> {
+        let garden = input.parse::<Garden>()?;
+        garden.walk(64, progress)
     }
 
-    fn part_two(&self) -> Result<usize> {
-        let garden = self.input().get_as::<Garden>()?;
-        Ok(garden.walk(26501365))
+    fn part_two_with_progress(&self, input: &str, progress: &Progress) -> Result<usize> {
+        let garden = input.parse::<Garden>()?;
+        garden.walk(26501365, progress)
+    }
+
+    fn visualize(&self, input: &str, viz: &Visualizer) -> Result<()> {
+        let garden = input.parse::<Garden>()?;
+        garden.animate(64, viz);
+        Ok(())
     }
 }
 
-const OFFSETS: [Point; 4] = [
-    Point { x: 0, y: -1 },
-    Point { x: 1, y: 0 },
-    Point { x: 0, y: 1 },
-    Point { x: -1, y: 0 },
-];
+// How many tile widths' worth of counts to sample before extrapolating
+const SAMPLES: usize = 6;
 
 struct Garden {
-    rocks: HashSet<Point>,
-    start: Point,
+    rocks: HashSet<IPoint>,
+    start: IPoint,
     width: isize,
     height: isize,
 }
@@ -47,7 +71,7 @@ impl FromStr for Garden {
     fn from_str(s: &str) -> Result<Self> {
         let mut rocks = HashSet::new();
         let (mut width, mut height) = (0, 0);
-        let mut start = Point::new(0, 0);
+        let mut start = IPoint::new(0, 0);
 
         for (y, line) in s.lines().enumerate() {
             height = y as isize;
@@ -55,9 +79,9 @@ impl FromStr for Garden {
                 width = x as isize;
                 match c {
                     '#' => {
-                        rocks.insert(Point::new(width, height));
+                        rocks.insert(IPoint::new(width, height));
                     }
-                    'S' => start = Point::new(width, height),
+                    'S' => start = IPoint::new(width, height),
                     _ => (),
                 }
             }
@@ -75,93 +99,100 @@ impl FromStr for Garden {
     }
 }
 impl Garden {
-    fn step(
-        &self,
-        start: &HashSet<Point>,
-        visited: &mut HashMap<Point, HashSet<Point>>,
-    ) -> HashSet<Point> {
-        let mut steps = HashSet::new();
-        for point in start {
-            if let Some(pts) = visited.get(point) {
-                steps.extend(pts);
-                continue;
-            }
-            let mut history = HashSet::new();
-            for offset in OFFSETS {
-                let next = point + offset;
-                if self.has_rock(next) {
-                    continue;
-                }
-                history.insert(next);
-            }
-            steps.extend(&history);
-            visited.insert(*point, history);
-        }
-        steps
+    fn step(&self, point: &IPoint) -> Vec<IPoint> {
+        point.neighbors().filter(|&n| !self.has_rock(n)).collect()
     }
 
-    fn has_rock(&self, point: Point) -> bool {
-        let mut check = point;
-        if check.x < 0 {
-            check.x = (self.width + (check.x % self.width)) % self.width;
-        } else {
-            check.x = check.x % self.width;
-        }
-        if check.y < 0 {
-            check.y = (self.height + (check.y % self.height)) % self.height;
-        } else {
-            check.y = check.y % self.height;
+    fn has_rock(&self, point: IPoint) -> bool {
+        self.rocks
+            .contains(&point.rem_euclid(self.width, self.height))
+    }
+
+    /// Shows the reachable plots spreading out from the start a step at a time.
+    fn animate(&self, steps: usize, viz: &Visualizer) {
+        let distances = bfs_distances_within(self.start, steps, |p| self.step(p));
+        let (width, height) = (self.width as usize, self.height as usize);
+        for n in 0..=steps {
+            viz.show(|| {
+                let mut frame =
+                    Frame::filled(width, height, Cell::colored('.', Color::BrightBlack));
+                for &rock in &self.rocks {
+                    frame.set(rock, Cell::colored('#', Color::Green));
+                }
+                let mut reachable = 0;
+                for (&p, &d) in &distances {
+                    if d <= n && d % 2 == n % 2 {
+                        reachable += 1;
+                        let cell = match d == n {
+                            true => Cell::colored('O', Color::BrightWhite).bold(),
+                            false => Cell::colored('O', Color::BrightGreen),
+                        };
+                        frame.set(p, cell);
+                    }
+                }
+                frame.with_caption(format!("Step {n}: {reachable} plots"))
+            });
         }
-        self.rocks.contains(&check)
     }
 
-    fn get_start(&self) -> HashSet<Point> {
-        HashSet::from_iter([self.start])
+    /// What `walk` should get, by taking every step.
+    #[cfg(test)]
+    fn walk_slowly(&self, to: usize) -> usize {
+        let mut reached = HashSet::from([self.start]);
+        for _ in 0..to {
+            reached = reached.iter().flat_map(|p| self.step(p)).collect();
+        }
+        reached.len()
     }
 
-    fn walk(&self, to: usize) -> usize {
-        let mut steps = self.get_start();
-        let mut visited = HashMap::new();
+    fn walk(&self, to: usize, progress: &Progress) -> Result<usize> {
+        // Every time the steps cover another whole tile width, the reachable
+        // area grows by a quadratic amount (once it's got going, anyway). So
+        // sample the counts for `to % width` steps plus 0, 1, 2... widths and
+        // carry the polynomial on out to `to`.
         let w = self.width as usize;
-        let z = w / 2 + w % 2;
-        let threshold = w + z;
-        if to > threshold {
-            let mut points = vec![];
-            for i in 0..to {
-                steps = self.step(&steps, &mut visited);
-                if (i + z) % w == 0 {
-                    let p = steps.len() as isize;
-                    points.push(p);
-                }
-                if points.len() == 3 {
-                    break;
-                }
-            }
-            let (a, b, c) = solve_quadratic(points[0], points[1], points[2]);
-            let x = ((to - z) / w) as isize;
-            ((a * x.pow(2)) - (b * x) + c) as usize
-        } else {
-            for _ in 1..=to {
-                steps = self.step(&steps, &mut visited);
-            }
-            steps.len()
+        let (r, k) = (to % w, to / w);
+        let sampled = k.min(SAMPLES - 1);
+        // One for the search, then one per sample counted
+        progress.set_total(sampled + 2);
+        progress.message(format!("searching {} steps out", r + sampled * w));
+        let distances = bfs_distances_within(self.start, r + sampled * w, |p| self.step(p));
+        progress.inc(1);
+        progress.message("counting plots");
+        // The garden is bipartite, so anywhere first reached in n steps or fewer
+        // with the same parity can be revisited by stepping back and forth
+        let counts = (0..=sampled)
+            .map(|i| {
+                let n = r + i * w;
+                let count = distances
+                    .values()
+                    .filter(|&&d| d <= n && d % 2 == n % 2)
+                    .count() as i128;
+                progress.inc(1);
+                count
+            })
+            .collect_vec();
+        if k == sampled {
+            return Ok(counts[k] as usize);
+        }
+        // Skip the first couple in case it takes a while to settle down
+        match degree(&counts[2..]) {
+            Some(d) if d <= 2 => extrapolate(&counts[2..], (k - 2) as i128)
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or(anyhow!("Too many plots to count after {k} steps")),
+            _ => Err(anyhow!(
+                "Reachable plots don't grow quadratically: {counts:?}"
+            )),
         }
     }
 }
 
-fn solve_quadratic(x: isize, y: isize, z: isize) -> (isize, isize, isize) {
-    let (mut a, mut b, mut c) = (x, y, z);
-    b = ((-4 * a) + b) / -2;
-    c = ((-9 * a) + 6 * b) + c;
-    b += -c + (-c / 2);
-    a += -b + -c;
-    println!("{a}, {b}, {c}");
-    (a as isize, b as isize, c as isize)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::differential;
+    use proptest::{collection::vec, prelude::*};
+    use std::{cell::RefCell, rc::Rc};
 
     const EXAMPLE_INPUT: &str = "...........
 .....###.#.
@@ -179,8 +210,8 @@ mod tests {
     fn should_parse() -> Result<()> {
         let test = "..#.#\n#.S..".parse::<Garden>()?;
         let expected_rocks =
-            HashSet::from_iter([Point::new(2, 0), Point::new(4, 0), Point::new(0, 1)]);
-        let expected_start = Point::new(2, 1);
+            HashSet::from_iter([IPoint::new(2, 0), IPoint::new(4, 0), IPoint::new(0, 1)]);
+        let expected_start = IPoint::new(2, 1);
         assert_eq!(expected_start, test.start);
         assert_eq!(expected_rocks, test.rocks);
         assert_eq!(5, test.width);
@@ -191,8 +222,8 @@ mod tests {
     #[test]
     fn should_step() -> Result<()> {
         let test = "..#.#\n#.S..".parse::<Garden>()?;
-        let steps = test.step(&test.get_start(), &mut HashMap::new());
-        let expected_steps = HashSet::from_iter([Point::new(1, 1), Point::new(3, 1)]);
+        let steps: HashSet<IPoint> = HashSet::from_iter(test.step(&test.start));
+        let expected_steps = HashSet::from_iter([IPoint::new(1, 1), IPoint::new(3, 1)]);
         assert_eq!(expected_steps, steps);
         Ok(())
     }
@@ -200,27 +231,81 @@ mod tests {
     #[test]
     fn should_solve_part1() -> Result<()> {
         let garden = EXAMPLE_INPUT.parse::<Garden>()?;
-        let steps = garden.walk(6);
+        let steps = garden.walk(6, &Progress::hidden())?;
         assert_eq!(16, steps);
         Ok(())
     }
 
+    #[test]
+    fn should_animate() -> Result<()> {
+        let garden = EXAMPLE_INPUT.parse::<Garden>()?;
+        let frames = Rc::new(RefCell::new(vec![]));
+        garden.animate(6, &Visualizer::new(frames.clone()));
+        let frames = frames.borrow();
+        assert_eq!(7, frames.len());
+        assert_eq!("Step 0: 1 plots", frames[0].caption());
+        assert_eq!("Step 6: 16 plots", frames[6].caption());
+        // One step either way from the start, apart from where the rocks are
+        assert_eq!("....#O#....", frames[1].plain_lines()[4]);
+        assert_eq!(".##.O.####.", frames[1].plain_lines()[5]);
+        Ok(())
+    }
+
     #[test]
     fn should_check_rocks() -> Result<()> {
         let test = "..#.#\n#.S..".parse::<Garden>()?;
-        assert_eq!(true, test.has_rock(Point::new(2, 0)));
-        assert_eq!(false, test.has_rock(Point::new(0, 0)));
-        assert_eq!(true, test.has_rock(Point::new(2, 2)));
-        assert_eq!(false, test.has_rock(Point::new(-2, 0)));
-        assert_eq!(true, test.has_rock(Point::new(2, -4)));
+        assert_eq!(true, test.has_rock(IPoint::new(2, 0)));
+        assert_eq!(false, test.has_rock(IPoint::new(0, 0)));
+        assert_eq!(true, test.has_rock(IPoint::new(2, 2)));
+        assert_eq!(false, test.has_rock(IPoint::new(-2, 0)));
+        assert_eq!(true, test.has_rock(IPoint::new(2, -4)));
         Ok(())
     }
 
     #[test]
     fn should_solve_part2() -> Result<()> {
         let garden = EXAMPLE_INPUT.parse::<Garden>()?;
-        let steps = garden.walk(50);
+        let steps = garden.walk(50, &Progress::hidden())?;
         assert_eq!(1594, steps);
         Ok(())
     }
+
+    /// Square gardens shaped like the real ones: an odd width, the start in
+    /// the middle, and clear paths along its row and column and round the
+    /// edge. Extrapolating relies on all of those.
+    fn garden() -> impl Strategy<Value = String> {
+        (2..5usize).prop_flat_map(|half| {
+            let w = half * 2 + 1;
+            vec(prop::bool::weighted(0.2), w * w).prop_map(move |rocks| {
+                (0..w)
+                    .map(|y| {
+                        (0..w)
+                            .map(|x| match (x, y) {
+                                _ if x == half && y == half => 'S',
+                                _ if x == 0 || y == 0 || x == w - 1 || y == w - 1 => '.',
+                                _ if x == half || y == half => '.',
+                                _ if rocks[y * w + x] => '#',
+                                _ => '.',
+                            })
+                            .collect::<String>()
+                    })
+                    .join("\n")
+            })
+        })
+    }
+
+    #[test]
+    fn should_walk_differentially() {
+        // Stepping out a hundred times is slow without optimizations, so run
+        // an eighth as many cases as usual (which PROPTEST_CASES still scales)
+        let config = ProptestConfig::with_cases(ProptestConfig::default().cases / 8);
+        differential::check_with("day21", config, (garden(), 0..100usize), |(input, to)| {
+            let garden = input.parse::<Garden>().unwrap();
+            // Refusing to extrapolate is fine, getting it wrong isn't
+            if let Ok(walked) = garden.walk(*to, &Progress::hidden()) {
+                prop_assert_eq!(garden.walk_slowly(*to), walked);
+            }
+            Ok(())
+        });
+    }
 }
@@ -19,3 +19,37 @@ pub mod day18;
 pub mod day19;
 pub mod day20;
 pub mod day21;
+
+use crate::solver::{boxed, DynSolver};
+
+/// Every day that's been done so far, in order.
+pub fn all() -> Vec<Box<dyn DynSolver>> {
+    vec![
+        boxed::<day01::Solution>(),
+        boxed::<day02::Solution>(),
+        boxed::<day03::Solution>(),
+        boxed::<day04::Solution>(),
+        boxed::<day05::Solution>(),
+        boxed::<day06::Solution>(),
+        boxed::<day07::Solution>(),
+        boxed::<day08::Solution>(),
+        boxed::<day09::Solution>(),
+        boxed::<day10::Solution>(),
+        boxed::<day11::Solution>(),
+        boxed::<day12::Solution>(),
+        boxed::<day13::Solution>(),
+        boxed::<day14::Solution>(),
+        boxed::<day15::Solution>(),
+        boxed::<day16::Solution>(),
+        boxed::<day17::Solution>(),
+        boxed::<day18::Solution>(),
+        boxed::<day19::Solution>(),
+        boxed::<day20::Solution>(),
+        boxed::<day21::Solution>(),
+    ]
+}
+
+/// A solver of its own for one day, if that day's been done.
+pub fn get(day: u8) -> Option<Box<dyn DynSolver>> {
+    all().into_iter().find(|s| s.day() == day)
+}
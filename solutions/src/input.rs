@@ -1,11 +1,33 @@
 // Stop warning me about unused code in this file damnit
 #![allow(dead_code)]
 
-use anyhow::Result;
+use crate::crypt;
+use anyhow::{anyhow, Result};
+use chacha20poly1305::Key;
+use lazy_static::lazy_static;
+use memmap2::Mmap;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    static ref CACHE: Mutex<HashMap<PathBuf, Arc<str>>> = Mutex::new(HashMap::new());
+}
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns the process-wide input cache on or off. Disabling it also drops
+/// anything already cached, so every subsequent read goes back to disk.
+pub fn set_caching(enabled: bool) {
+    CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+    if !enabled {
+        CACHE.lock().unwrap().clear();
+    }
+}
 
 pub struct AdventInput {
     file: PathBuf,
@@ -16,23 +38,99 @@ impl AdventInput {
         AdventInput { file }
     }
 
-    pub fn get(&self) -> Result<String> {
-        Ok(fs::read_to_string(&self.file)?)
+    pub fn path(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn encrypted_path(&self) -> PathBuf {
+        let mut name = self.file.clone().into_os_string();
+        name.push(".");
+        name.push(crypt::EXTENSION);
+        PathBuf::from(name)
+    }
+
+    /// Writes an encrypted copy of the plaintext input alongside it. Returns
+    /// false if there's no plaintext input to encrypt.
+    pub fn encrypt(&self, key: &Key) -> Result<bool> {
+        if !self.file.exists() {
+            return Ok(false);
+        }
+        let encrypted = crypt::encrypt(key, &fs::read(&self.file)?)?;
+        fs::write(self.encrypted_path(), encrypted)?;
+        Ok(true)
+    }
+
+    /// Restores the plaintext input from its encrypted copy. Returns false if
+    /// there's no encrypted input to decrypt. A plaintext input that's already
+    /// there (and might have been edited) is only replaced if `force` is set.
+    pub fn decrypt(&self, key: &Key, force: bool) -> Result<bool> {
+        let encrypted = self.encrypted_path();
+        if !encrypted.exists() {
+            return Ok(false);
+        }
+        if self.file.exists() && !force {
+            return Err(anyhow!(
+                "Not overwriting {} (use --force)",
+                self.file.display()
+            ));
+        }
+        let decrypted = crypt::decrypt(key, &fs::read(encrypted)?)?;
+        fs::write(&self.file, decrypted)?;
+        Ok(true)
+    }
+
+    /// Whether there's an input to read, either in plaintext or encrypted
+    /// along with a key to decrypt it.
+    pub fn is_available(&self) -> bool {
+        self.file.exists() || (self.encrypted_path().exists() && crypt::load_key().is_ok())
+    }
+
+    fn load(&self) -> Result<String> {
+        self.load_with(crypt::load_key)
+    }
+
+    // A plaintext input always wins; the encrypted copy is only consulted if
+    // that's missing (e.g. on a fresh clone), and only then is `key` asked for.
+    fn load_with(&self, key: impl FnOnce() -> Result<Key>) -> Result<String> {
+        let encrypted = self.encrypted_path();
+        if self.file.exists() || !encrypted.exists() {
+            return Ok(fs::read_to_string(&self.file)?);
+        }
+        let decrypted = crypt::decrypt(&key()?, &fs::read(encrypted)?)?;
+        Ok(String::from_utf8(decrypted)?)
+    }
+
+    // Both parts (and every perf iteration) ask for the same file, so only
+    // hit the disk the first time unless we've been told not to.
+    fn read(&self) -> Result<Arc<str>> {
+        if !CACHE_ENABLED.load(Ordering::Relaxed) {
+            return Ok(self.load()?.into());
+        }
+        let mut cache = CACHE.lock().unwrap();
+        if let Some(contents) = cache.get(&self.file) {
+            return Ok(contents.clone());
+        }
+        let contents: Arc<str> = self.load()?.into();
+        cache.insert(self.file.clone(), contents.clone());
+        Ok(contents)
+    }
+
+    /// The whole input. It's shared with the cache rather than copied, so
+    /// asking again (say, on every timed run) costs next to nothing.
+    pub fn get(&self) -> Result<Arc<str>> {
+        self.read()
     }
 
     pub fn get_as<T>(&self) -> Result<T, T::Err>
     where
         T: FromStr,
     {
-        let read = fs::read_to_string(&self.file).unwrap();
+        let read = self.read().unwrap();
         T::from_str(&read)
     }
 
     pub fn get_csv(&self) -> Result<Vec<String>> {
-        Ok(fs::read_to_string(&self.file)?
-            .split(',')
-            .map(|s| s.to_owned())
-            .collect())
+        Ok(csv(&self.read()?))
     }
 
     pub fn get_csv_as<T>(&self) -> Result<Vec<T>>
@@ -40,17 +138,11 @@ impl AdventInput {
         T: FromStr,
         <T as FromStr>::Err: Debug,
     {
-        Ok(fs::read_to_string(&self.file)?
-            .split(',')
-            .map(|v| v.parse::<T>().unwrap())
-            .collect())
+        csv_as(&self.read()?)
     }
 
     pub fn get_lines(&self) -> Result<Vec<String>> {
-        Ok(fs::read_to_string(&self.file)?
-            .lines()
-            .map(|l| l.to_owned())
-            .collect())
+        Ok(lines(&self.read()?))
     }
 
     pub fn get_lines_as<T>(&self) -> Result<Vec<T>>
@@ -58,8 +150,7 @@ impl AdventInput {
         T: FromStr,
         <T as FromStr>::Err: Debug,
     {
-        let read = fs::read_to_string(&self.file)?;
-        Ok(read.lines().map(|x| x.parse::<T>().unwrap()).collect())
+        lines_as(&self.read()?)
     }
 
     pub fn get_grouped_as<T>(&self) -> Result<Vec<T>>
@@ -67,10 +158,204 @@ impl AdventInput {
         T: FromStr,
         <T as FromStr>::Err: Debug,
     {
-        let read = fs::read_to_string(&self.file)?;
-        Ok(read
-            .split("\n\n")
-            .map(|x| x.parse::<T>().unwrap())
-            .collect())
+        grouped_as(&self.read()?)
+    }
+
+    // The following bypass the cache entirely: they exist for inputs that are
+    // too big to want a second copy of (or to fit in memory at all). They also
+    // only work on plaintext inputs, since decrypting means reading it all.
+
+    /// Streams the input one line at a time without loading the whole file.
+    pub fn lines_iter(&self) -> Result<impl Iterator<Item = Result<String>>> {
+        let reader = BufReader::new(File::open(&self.file)?);
+        Ok(reader.lines().map(|l| Ok(l?)))
+    }
+
+    /// Streams the input as records separated by `delimiter`, which is not
+    /// included in the yielded records.
+    pub fn records_iter(&self, delimiter: u8) -> Result<impl Iterator<Item = Result<Vec<u8>>>> {
+        let reader = BufReader::new(File::open(&self.file)?);
+        Ok(reader.split(delimiter).map(|r| Ok(r?)))
+    }
+
+    /// Streams the input as fixed-size records. The final record may be
+    /// shorter than `size` if the file length isn't an exact multiple.
+    pub fn chunks_iter(&self, size: usize) -> Result<impl Iterator<Item = Result<Vec<u8>>>> {
+        let mut reader = BufReader::new(File::open(&self.file)?);
+        Ok(std::iter::from_fn(move || {
+            let mut chunk = Vec::with_capacity(size);
+            match (&mut reader).take(size as u64).read_to_end(&mut chunk) {
+                Ok(0) => None,
+                Ok(_) => Some(Ok(chunk)),
+                Err(e) => Some(Err(e.into())),
+            }
+        }))
+    }
+
+    /// Maps the input file into memory and returns a read-only byte view.
+    pub fn mmap(&self) -> Result<Mmap> {
+        let file = File::open(&self.file)?;
+        // SAFETY: inputs aren't modified while a solver is running; if one is
+        // truncated out from under us, that's on whoever did it.
+        Ok(unsafe { Mmap::map(&file)? })
+    }
+}
+
+// The same ways of cutting up an input as the `get_*` methods above, for when
+// it didn't come from a file (e.g. a puzzle's example in a test).
+
+pub fn csv(input: &str) -> Vec<String> {
+    input.split(',').map(|s| s.to_owned()).collect()
+}
+
+pub fn csv_as<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    input.split(',').map(parse_part).collect()
+}
+
+pub fn lines(input: &str) -> Vec<String> {
+    input.lines().map(|l| l.to_owned()).collect()
+}
+
+pub fn lines_as<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    input.lines().map(parse_part).collect()
+}
+
+pub fn grouped_as<T>(input: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    input.split("\n\n").map(parse_part).collect()
+}
+
+fn parse_part<T>(part: &str) -> Result<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    part.parse::<T>()
+        .map_err(|e| anyhow!("Couldn't parse {part:?}: {e:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn with_file(name: &str, contents: &str) -> Result<AdventInput> {
+        let file = std::env::temp_dir().join(format!("advent_input_{name}.txt"));
+        fs::write(&file, contents)?;
+        Ok(AdventInput { file })
+    }
+
+    #[test]
+    fn should_split_strings() -> Result<()> {
+        assert_eq!(vec!["a", "b"], lines("a\nb\n"));
+        assert_eq!(vec![1, 2, 3], csv_as::<u8>("1,2,3")?);
+        assert_eq!(vec![12, 3], grouped_as::<u8>("12\n\n3")?);
+        assert!(lines_as::<u8>("1\nx").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_share_cached_input() -> Result<()> {
+        let input = with_file("shared", "some input")?;
+        let first = input.get()?;
+        assert_eq!("some input", &*first);
+        assert!(Arc::ptr_eq(&first, &input.get()?));
+        Ok(())
+    }
+
+    #[test]
+    fn should_stream_lines() -> Result<()> {
+        let input = with_file("lines", "one\ntwo\nthree\n")?;
+        let lines: Vec<String> = input.lines_iter()?.try_collect()?;
+        assert_eq!(vec!["one", "two", "three"], lines);
+        Ok(())
+    }
+
+    #[test]
+    fn should_stream_records() -> Result<()> {
+        let input = with_file("records", "a,bc,def")?;
+        let records: Vec<Vec<u8>> = input.records_iter(b',')?.try_collect()?;
+        assert_eq!(
+            vec![b"a".to_vec(), b"bc".to_vec(), b"def".to_vec()],
+            records
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_stream_chunks() -> Result<()> {
+        let input = with_file("chunks", "abcdefg")?;
+        let chunks: Vec<Vec<u8>> = input.chunks_iter(3)?.try_collect()?;
+        assert_eq!(
+            vec![b"abc".to_vec(), b"def".to_vec(), b"g".to_vec()],
+            chunks
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_encrypted() -> Result<()> {
+        let key = *Key::from_slice(&[3; 32]);
+        let input = with_file("encrypted", "secret input")?;
+        assert!(input.encrypt(&key)?);
+        fs::remove_file(input.path())?;
+        assert!(input.decrypt(&key, false)?);
+        assert_eq!("secret input", fs::read_to_string(input.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_only_overwrite_when_forced() -> Result<()> {
+        let key = *Key::from_slice(&[4; 32]);
+        let input = with_file("overwrite", "secret input")?;
+        assert!(input.encrypt(&key)?);
+        fs::write(input.path(), "edited input")?;
+        assert!(input.decrypt(&key, false).is_err());
+        assert_eq!("edited input", fs::read_to_string(input.path())?);
+        assert!(input.decrypt(&key, true)?);
+        assert_eq!("secret input", fs::read_to_string(input.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn should_fall_back_to_encrypted() -> Result<()> {
+        let key = *Key::from_slice(&[5; 32]);
+        let input = with_file("fallback", "secret input")?;
+        assert!(input.encrypt(&key)?);
+        fs::write(input.path(), "plaintext wins")?;
+        assert_eq!("plaintext wins", input.load_with(|| Ok(key))?);
+        fs::remove_file(input.path())?;
+        assert_eq!("secret input", input.load_with(|| Ok(key))?);
+        // Reading it shouldn't leave a decrypted copy lying around
+        assert!(!input.path().exists());
+        assert!(input.load_with(|| Err(anyhow!("No key"))).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_know_when_missing() -> Result<()> {
+        let input = with_file("missing", "")?;
+        assert!(input.is_available());
+        fs::remove_file(input.path())?;
+        assert!(!input.is_available());
+        Ok(())
+    }
+
+    #[test]
+    fn should_map() -> Result<()> {
+        let input = with_file("mmap", "hello")?;
+        assert_eq!(b"hello", &input.mmap()?[..]);
+        Ok(())
     }
 }
@@ -0,0 +1,13 @@
+extern crate anyhow;
+extern crate impl_ops;
+
+pub mod answer;
+pub mod common;
+pub mod common_ops;
+pub mod crypt;
+pub mod days;
+pub mod input;
+pub mod progress;
+pub mod render;
+pub mod solver;
+pub mod viz;
@@ -1,23 +1,42 @@
-extern crate anyhow;
-extern crate impl_ops;
-
-use anyhow::Result;
-use clap::{arg, command, Parser, Subcommand};
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
 use itertools::Itertools;
 use prettytable::{color, format::Alignment, row, Attr, Cell, Row, Table};
-use solver::{Measure, Solver};
-
-mod common;
-mod common_ops;
-mod days;
-mod input;
-mod solver;
+use solutions::{
+    crypt,
+    days::{self, day02},
+    input,
+    progress::Progress,
+    render::{Palette, Renderer},
+    viz::{Terminal, Visualizer},
+};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+// --visualize and its options mean nothing to the subcommands
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Animate one day's puzzle in the terminal instead of solving them all
+    #[arg(long, value_name = "DAY")]
+    visualize: Option<u8>,
+    /// Frames per second for --visualize
+    #[arg(long, default_value_t = 10.0, requires = "visualize")]
+    fps: f32,
+    /// Start --visualize paused, to step through one frame at a time
+    #[arg(long, requires = "visualize")]
+    paused: bool,
+    /// Write --visualize to a .png, .svg (last frame) or .gif (every frame)
+    #[arg(long, value_name = "PATH", requires = "visualize")]
+    render: Option<PathBuf>,
+    /// Pixels per tile for --render
+    #[arg(long, default_value_t = 4, requires = "visualize")]
+    scale: usize,
+    /// Tile colours for --render, e.g. '#=ff8800,.=202020'
+    #[arg(long, default_value = "", requires = "visualize")]
+    palette: Palette,
 }
 
 #[derive(Subcommand)]
@@ -27,40 +46,125 @@ enum Commands {
         fine: bool,
         #[arg(short, long, default_value_t = 10)]
         iterations: u8,
+        /// Re-read each input from disk on every run instead of caching it
+        #[arg(short, long)]
+        cold: bool,
+    },
+    /// Manage the encrypted copies of the puzzle inputs
+    Inputs {
+        #[command(subcommand)]
+        action: InputsAction,
+    },
+    /// Dig further into day 2's cube games
+    Day02 {
+        #[command(subcommand)]
+        action: Day02Action,
+    },
+}
+
+#[derive(Subcommand)]
+enum Day02Action {
+    /// Check every game against a bag, saying why the impossible ones were
+    Query {
+        /// What's in the bag, e.g. '12 red, 13 green, 14 blue'
+        #[arg(long, default_value = "12 red, 13 green, 14 blue")]
+        bag: day02::Cubes,
+        /// Also work out the fewest cubes to add so N games are possible
+        #[arg(long, value_name = "N")]
+        possible: Option<usize>,
+        /// Read the games from here instead of inputs/day02.txt
+        #[arg(long)]
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum InputsAction {
+    /// Write inputs/dayNN.txt.enc for every plaintext input
+    Encrypt,
+    /// Restore inputs/dayNN.txt from every encrypted input
+    Decrypt {
+        /// Overwrite plaintext inputs that are already there
+        #[arg(long)]
+        force: bool,
     },
+    /// Generate a new key and save it to the keyfile
+    Keygen,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if let Some(day) = cli.visualize {
+        let viz = match cli.render {
+            Some(path) => Visualizer::new(Renderer::new(path, cli.palette, cli.scale, cli.fps)?),
+            None => Visualizer::new(Terminal::new(cli.fps, cli.paused)),
+        };
+        return visualize(day, viz);
+    }
     match cli.command {
-        Some(Commands::Perf { fine, iterations }) => perf(fine, iterations)?,
+        Some(Commands::Perf {
+            fine,
+            iterations,
+            cold,
+        }) => {
+            input::set_caching(!cold);
+            perf(fine, iterations)?
+        }
+        Some(Commands::Inputs { action }) => inputs(action)?,
+        Some(Commands::Day02 { action }) => day02(action)?,
         _ => solve()?,
     }
     Ok(())
 }
 
 fn solve() -> Result<()> {
-    days::day01::Solution::new().solve()?;
-    days::day02::Solution::new().solve()?;
-    days::day03::Solution::new().solve()?;
-    days::day04::Solution::new().solve()?;
-    days::day05::Solution::new().solve()?;
-    days::day06::Solution::new().solve()?;
-    days::day07::Solution::new().solve()?;
-    days::day08::Solution::new().solve()?;
-    days::day09::Solution::new().solve()?;
-    days::day10::Solution::new().solve()?;
-    days::day11::Solution::new().solve()?;
-    days::day12::Solution::new().solve()?;
-    days::day13::Solution::new().solve()?;
-    days::day14::Solution::new().solve()?;
-    days::day15::Solution::new().solve()?;
-    days::day16::Solution::new().solve()?;
-    days::day17::Solution::new().solve()?;
-    days::day18::Solution::new().solve()?;
-    days::day19::Solution::new().solve()?;
-    days::day20::Solution::new().solve()?;
-    days::day21::Solution::new().solve()?;
+    for solver in days::all() {
+        solver.solve()?;
+    }
+    Ok(())
+}
+
+fn visualize(day: u8, viz: Visualizer) -> Result<()> {
+    let solver = days::get(day).ok_or(anyhow!("Day {day} hasn't been done yet"))?;
+    solver.animate(&viz)
+}
+
+fn inputs(action: InputsAction) -> Result<()> {
+    if let InputsAction::Keygen = action {
+        let path = crypt::keyfile_path();
+        if path.exists() {
+            return Err(anyhow!("{} already exists", path.display()));
+        }
+        std::fs::write(&path, crypt::generate_key())?;
+        println!("Wrote new input key to {}", path.display());
+        return Ok(());
+    }
+    let key = crypt::load_key()?;
+    for day in 1..=25 {
+        let input = input::AdventInput::for_day(day);
+        let done = match action {
+            InputsAction::Encrypt => input.encrypt(&key)?,
+            InputsAction::Decrypt { force } => input.decrypt(&key, force)?,
+            InputsAction::Keygen => unreachable!(),
+        };
+        if done {
+            println!("Day {day:02}: done");
+        }
+    }
+    Ok(())
+}
+
+fn day02(action: Day02Action) -> Result<()> {
+    let Day02Action::Query {
+        bag,
+        possible,
+        input: path,
+    } = action;
+    let games = match path {
+        Some(path) => std::fs::read_to_string(path)?.into(),
+        None => input::AdventInput::for_day(2).get()?,
+    };
+    println!("{}", day02::query(&games, &bag, possible)?);
     Ok(())
 }
 
@@ -69,58 +173,45 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
         true => format_fine,
         false => format_rough,
     };
-    let measures: Vec<_> = vec![
-        Measure::get(days::day01::Solution::new()),
-        Measure::get(days::day02::Solution::new()),
-        Measure::get(days::day03::Solution::new()),
-        Measure::get(days::day04::Solution::new()),
-        Measure::get(days::day05::Solution::new()),
-        Measure::get(days::day06::Solution::new()),
-        Measure::get(days::day07::Solution::new()),
-        Measure::get(days::day08::Solution::new()),
-        Measure::get(days::day09::Solution::new()),
-        Measure::get(days::day10::Solution::new()),
-        Measure::get(days::day11::Solution::new()),
-        Measure::get(days::day12::Solution::new()),
-        Measure::get(days::day13::Solution::new()),
-        Measure::get(days::day14::Solution::new()),
-        Measure::get(days::day15::Solution::new()),
-        Measure::get(days::day16::Solution::new()),
-        Measure::get(days::day17::Solution::new()),
-        Measure::get(days::day18::Solution::new()),
-        Measure::get(days::day19::Solution::new()),
-        Measure::get(days::day20::Solution::new()),
-        Measure::get(days::day21::Solution::new()),
-    ];
-    let count = measures.len();
+    let solvers = days::all();
+    let count = solvers.len();
 
     println!("Generating performance statistics...");
     println!("{count} solutions, {iterations} runs each.\n");
 
     let mut results = vec![];
-    for (i, m) in measures.iter().enumerate() {
-        print!("\rProcessing... {}/{count}", i + 1);
-        results.push((
-            m.title(),
-            m.describe_part_one(),
-            m.time_part_one(iterations)?,
-        ));
-        results.push((
-            m.title(),
-            m.describe_part_two(),
-            m.time_part_two(iterations)?,
-        ));
+    let mut notes = vec![];
+    let progress = Progress::for_terminal("Processing");
+    progress.set_total(count);
+    for solver in solvers.iter() {
+        progress.message(solver.describe());
+        for part in [1, 2] {
+            let time = solver.time_part(part, iterations)?;
+            // Stats come from a run of their own, so they don't skew the timing
+            if time.is_some() {
+                if let Some(note) = solver.stats(part)? {
+                    notes.push(format!("{}: {note}", solver.describe_part(part)));
+                }
+            }
+            results.push((solver.title(), solver.describe_part(part), time));
+        }
+        progress.inc(1);
     }
-    println!("\nDone.");
+    progress.finish();
+    println!("Done.");
 
-    // Get some rough stats
-    let len = results.len();
-    let (i1, i2, i3) = (len / 2, (len / 4) * 2, (len * 90) / 100);
+    // Get some rough stats, leaving out anything that isn't implemented yet
     let ranked = results
         .iter()
-        .sorted_by(|a, b| a.1.cmp(&b.1))
-        .map(|(_, _, d)| d.as_secs_f32())
+        .filter_map(|(_, _, d)| d.map(|d| d.as_secs_f32()))
+        .sorted_by(|a, b| a.total_cmp(b))
         .collect_vec();
+    if ranked.is_empty() {
+        println!("Nothing to time yet.");
+        return Ok(());
+    }
+    let len = ranked.len();
+    let (i1, i2, i3) = (len / 2, (len / 4) * 2, (len * 90) / 100);
     let (p1, p2, p3) = (ranked[i1], ranked[i2], ranked[i3]);
 
     let get_quartile_color = |d: f32| match d {
@@ -142,13 +233,20 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
     for (title, key, res) in results {
         let s = key.split(' ').collect_vec();
         let (day, part) = (s[1], s[3]);
-        let dur = res.as_secs_f32();
+        let time = match res {
+            Some(d) => {
+                let dur = d.as_secs_f32();
+                Cell::new(&fmt_func(&dur))
+                    .with_style(Attr::ForegroundColor(get_quartile_color(dur)))
+            }
+            None => Cell::new("n/a"),
+        };
         table.add_row(Row::new(vec![
             Cell::new("2023"),
             Cell::new(day),
-            Cell::new(&title),
+            Cell::new(title),
             Cell::new(part),
-            Cell::new(&fmt_func(&dur)).with_style(Attr::ForegroundColor(get_quartile_color(dur))),
+            time,
         ]));
     }
 
@@ -175,6 +273,10 @@ fn perf(fine: bool, iterations: u8) -> Result<()> {
 
     table.printstd();
 
+    if !notes.is_empty() {
+        println!("\n{}", notes.join("\n"));
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,162 @@
+// Lets slow solvers say how they're getting on. Whoever runs them decides
+// whether anyone sees it: a live bar on stderr when that's a terminal, and
+// nothing at all otherwise (e.g. while timing them).
+
+use std::{
+    cell::RefCell,
+    io::{stderr, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+const BAR_WIDTH: usize = 30;
+// Redrawing on every update would slow down the loops doing the updating
+const REDRAW_EVERY: Duration = Duration::from_millis(50);
+
+pub struct Progress {
+    bar: Option<RefCell<Bar>>,
+}
+impl Progress {
+    /// A handle that quietly ignores everything it's told.
+    pub fn hidden() -> Self {
+        Progress { bar: None }
+    }
+
+    /// A live bar on stderr if that's a terminal, otherwise hidden.
+    pub fn for_terminal(label: &str) -> Self {
+        match stderr().is_terminal() {
+            true => Progress {
+                bar: Some(RefCell::new(Bar::new(label))),
+            },
+            false => Progress::hidden(),
+        }
+    }
+
+    /// How many steps there are altogether, if that's known.
+    pub fn set_total(&self, total: usize) {
+        self.update(|bar| bar.total = Some(total));
+    }
+
+    pub fn inc(&self, n: usize) {
+        self.update(|bar| bar.done += n);
+    }
+
+    pub fn message(&self, message: impl Into<String>) {
+        self.update(|bar| bar.message = message.into());
+    }
+
+    /// Like `message`, but the message is only built if it's about to be
+    /// drawn, so it's fine to call this on every step.
+    pub fn message_with(&self, message: impl FnOnce() -> String) {
+        if self.bar.as_ref().is_some_and(|bar| bar.borrow().is_due()) {
+            self.message(message());
+        }
+    }
+
+    /// Clears the bar away. Happens anyway when the handle is dropped.
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            let mut bar = bar.borrow_mut();
+            if bar.drawn.take().is_some() {
+                eprint!("\r\x1b[2K");
+            }
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Bar)) {
+        if let Some(bar) = &self.bar {
+            let mut bar = bar.borrow_mut();
+            f(&mut bar);
+            if bar.is_due() {
+                eprint!("\r\x1b[2K{}", bar.render());
+                let _ = stderr().flush();
+                bar.drawn = Some(Instant::now());
+            }
+        }
+    }
+}
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+struct Bar {
+    label: String,
+    total: Option<usize>,
+    done: usize,
+    message: String,
+    /// When the bar was last drawn, or None if it isn't on screen.
+    drawn: Option<Instant>,
+}
+impl Bar {
+    fn new(label: &str) -> Self {
+        Bar {
+            label: label.to_string(),
+            total: None,
+            done: 0,
+            message: String::new(),
+            drawn: None,
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        self.drawn.is_none_or(|t| t.elapsed() >= REDRAW_EVERY)
+    }
+
+    fn render(&self) -> String {
+        let count = match self.total {
+            Some(total) => {
+                let filled = match total {
+                    0 => BAR_WIDTH,
+                    _ => BAR_WIDTH * self.done.min(total) / total,
+                };
+                format!(
+                    "[{}{}] {}/{total}",
+                    "#".repeat(filled),
+                    ".".repeat(BAR_WIDTH - filled),
+                    self.done
+                )
+            }
+            None => self.done.to_string(),
+        };
+        match self.message.is_empty() {
+            true => format!("{}: {count}", self.label),
+            false => format!("{}: {count} {}", self.label, self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render() {
+        let mut bar = Bar::new("Day 01 Part 01");
+        bar.done = 3;
+        assert_eq!("Day 01 Part 01: 3", bar.render());
+        bar.total = Some(6);
+        bar.message = "sampling".to_string();
+        assert_eq!(
+            format!(
+                "Day 01 Part 01: [{}{}] 3/6 sampling",
+                "#".repeat(15),
+                ".".repeat(15)
+            ),
+            bar.render()
+        );
+        bar.done = 9;
+        assert!(bar.render().contains(&"#".repeat(BAR_WIDTH)));
+    }
+
+    #[test]
+    fn should_ignore_updates_when_hidden() {
+        let progress = Progress::hidden();
+        progress.set_total(10);
+        progress.inc(3);
+        progress.message("nobody's watching");
+        progress.message_with(|| unreachable!("nobody's watching"));
+        progress.finish();
+        assert!(progress.bar.is_none());
+    }
+}
@@ -0,0 +1,389 @@
+// Writing frames out to image files instead of the terminal: the last frame as
+// a PNG or SVG, or every frame as an animated GIF. Each cell becomes a square
+// of solid colour `scale` pixels across, so the characters themselves are lost.
+
+use crate::viz::{Cell, Frame, FrameSink};
+use anyhow::{anyhow, Error, Result};
+use colored::Color;
+use itertools::Itertools;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Write as _,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Svg,
+    Gif,
+}
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Ok(Format::Png),
+            "svg" => Ok(Format::Svg),
+            "gif" => Ok(Format::Gif),
+            _ => Err(anyhow!(
+                "Can't render to {}: use .png, .svg or .gif",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Which colour each kind of tile comes out as. Tiles it doesn't mention keep
+/// the colour they'd have in the terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    tiles: HashMap<char, Rgb>,
+    background: Rgb,
+}
+impl Palette {
+    pub fn new() -> Self {
+        let background = [24, 24, 32];
+        Palette {
+            tiles: HashMap::from([(' ', background)]),
+            background,
+        }
+    }
+
+    pub fn with(mut self, tile: char, color: Rgb) -> Self {
+        self.tiles.insert(tile, color);
+        self
+    }
+
+    pub fn color(&self, cell: &Cell) -> Rgb {
+        match self.tiles.get(&cell.ch) {
+            Some(&rgb) => rgb,
+            None => cell.color.map_or([204, 204, 204], terminal_rgb),
+        }
+    }
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new()
+    }
+}
+/// Comma-separated `tile=RRGGBB` pairs, e.g. `#=ff8800,.=000000`.
+impl FromStr for Palette {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut palette = Palette::new();
+        for entry in s.split(',').filter(|e| !e.is_empty()) {
+            let mut chars = entry.chars();
+            let (Some(tile), Some('=')) = (chars.next(), chars.next()) else {
+                return Err(anyhow!("Invalid palette entry: {entry}"));
+            };
+            let hex = chars.as_str();
+            let rgb = u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|_| hex.len() == 6)
+                .ok_or(anyhow!("Invalid colour in palette entry: {entry}"))?;
+            let [_, r, g, b] = rgb.to_be_bytes();
+            palette = palette.with(tile, [r, g, b]);
+        }
+        Ok(palette)
+    }
+}
+
+// Roughly what the usual terminal themes use
+fn terminal_rgb(color: Color) -> Rgb {
+    match color {
+        Color::Black => [0, 0, 0],
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+        Color::White => [229, 229, 229],
+        Color::BrightBlack => [102, 102, 102],
+        Color::BrightRed => [241, 76, 76],
+        Color::BrightGreen => [35, 209, 139],
+        Color::BrightYellow => [245, 245, 67],
+        Color::BrightBlue => [59, 142, 234],
+        Color::BrightMagenta => [214, 112, 214],
+        Color::BrightCyan => [41, 184, 219],
+        Color::BrightWhite => [255, 255, 255],
+        Color::TrueColor { r, g, b } => [r, g, b],
+    }
+}
+
+/// Writes frames to a file, picking the format from its extension.
+pub struct Renderer {
+    path: PathBuf,
+    format: Format,
+    palette: Palette,
+    scale: usize,
+    /// Hundredths of a second between GIF frames.
+    delay: u16,
+    last: Option<Frame>,
+    gif: Option<gif::Encoder<BufWriter<File>>>,
+    /// Anything that went wrong mid-stream, to report once it's all over.
+    error: Option<Error>,
+}
+impl Renderer {
+    pub fn new(path: impl Into<PathBuf>, palette: Palette, scale: usize, fps: f32) -> Result<Self> {
+        let path = path.into();
+        Ok(Renderer {
+            format: Format::from_path(&path)?,
+            path,
+            palette,
+            scale: scale.max(1),
+            delay: (100. / fps.max(0.1)).round().clamp(1., u16::MAX as f32) as u16,
+            last: None,
+            gif: None,
+            error: None,
+        })
+    }
+
+    /// The frame blown up to pixels, as (width, height, row-major colours).
+    fn pixels(&self, frame: &Frame) -> (usize, usize, Vec<Rgb>) {
+        let (width, height) = (frame.width() * self.scale, frame.height() * self.scale);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in frame.rows() {
+            let line = row
+                .iter()
+                .flat_map(|cell| [self.palette.color(cell)].repeat(self.scale))
+                .collect::<Vec<_>>();
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        (width, height, pixels)
+    }
+
+    fn add_gif_frame(&mut self, frame: &Frame) -> Result<()> {
+        let (width, height, pixels) = self.pixels(frame);
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => return Err(anyhow!("{width}x{height} is too big for a GIF")),
+        };
+        if self.gif.is_none() {
+            let file = BufWriter::new(File::create(&self.path)?);
+            let mut encoder = gif::Encoder::new(file, width, height, &[])?;
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            self.gif = Some(encoder);
+        }
+
+        let mut gif_frame = match Self::indexed(&pixels) {
+            Some((buffer, palette)) => gif::Frame {
+                width,
+                height,
+                buffer: Cow::Owned(buffer),
+                palette: Some(palette),
+                ..Default::default()
+            },
+            // Too colourful to index directly, so let the encoder pick the colours
+            None => gif::Frame::from_rgb_speed(width, height, &pixels.concat(), 10),
+        };
+        gif_frame.delay = self.delay;
+        self.gif.as_mut().unwrap().write_frame(&gif_frame)?;
+        Ok(())
+    }
+
+    /// Indexes the pixels against a palette of their own, as long as there
+    /// aren't more colours than a GIF can hold. Most frames only have a few.
+    fn indexed(pixels: &[Rgb]) -> Option<(Vec<u8>, Vec<u8>)> {
+        let mut colors: Vec<Rgb> = vec![];
+        let mut indices = HashMap::new();
+        let mut buffer = Vec::with_capacity(pixels.len());
+        for &rgb in pixels {
+            let index = *indices.entry(rgb).or_insert_with(|| {
+                colors.push(rgb);
+                colors.len() - 1
+            });
+            buffer.push(u8::try_from(index).ok()?);
+        }
+        Some((buffer, colors.concat()))
+    }
+
+    fn write_png(&self, frame: &Frame) -> Result<()> {
+        let (width, height, pixels) = self.pixels(frame);
+        let file = BufWriter::new(File::create(&self.path)?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&pixels.concat())?;
+        Ok(())
+    }
+
+    fn write_svg(&self, frame: &Frame) -> Result<()> {
+        fs::write(&self.path, self.svg(frame))?;
+        Ok(())
+    }
+
+    fn svg(&self, frame: &Frame) -> String {
+        let s = self.scale;
+        let (width, height) = (frame.width() * s, frame.height() * s);
+        let hex = |[r, g, b]: Rgb| format!("#{r:02x}{g:02x}{b:02x}");
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             shape-rendering=\"crispEdges\">\n"
+        );
+        if !frame.caption().is_empty() {
+            let caption = frame
+                .caption()
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            let _ = writeln!(svg, "<title>{caption}</title>");
+        }
+        let background = hex(self.palette.background);
+        let _ = writeln!(
+            svg,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"{background}\"/>"
+        );
+        // One rectangle per run of same-coloured cells keeps the file size down
+        for (y, row) in frame.rows().enumerate() {
+            let mut x = 0;
+            for (len, rgb) in row.iter().map(|c| self.palette.color(c)).dedup_with_count() {
+                if rgb != self.palette.background {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{s}\" fill=\"{}\"/>",
+                        x * s,
+                        y * s,
+                        len * s,
+                        hex(rgb)
+                    );
+                }
+                x += len;
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+impl FrameSink for Renderer {
+    fn show(&mut self, frame: &Frame) -> bool {
+        if self.format == Format::Gif {
+            if let Err(e) = self.add_gif_frame(frame) {
+                self.error = Some(e);
+                return false;
+            }
+        }
+        self.last = Some(frame.clone());
+        true
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let Some(last) = &self.last else {
+            return Err(anyhow!("Nothing to render"));
+        };
+        match self.format {
+            Format::Png => self.write_png(last),
+            Format::Svg => self.write_svg(last),
+            // Dropping the encoder writes out the end of the file
+            Format::Gif => {
+                self.gif.take();
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::IPoint;
+
+    fn frame() -> Frame {
+        let mut frame = Frame::filled(3, 2, Cell::plain('.'));
+        frame.set(IPoint::new(0, 0), Cell::colored('#', Color::Red));
+        frame.set(IPoint::new(1, 0), Cell::colored('#', Color::Red));
+        frame.set(IPoint::new(2, 1), Cell::plain(' '));
+        frame.with_caption("a < b")
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("advent_render_{name}"))
+    }
+
+    #[test]
+    fn should_parse_palettes() -> Result<()> {
+        let palette = "#=ff8800,.=000000".parse::<Palette>()?;
+        assert_eq!([255, 136, 0], palette.color(&Cell::plain('#')));
+        assert_eq!([0, 0, 0], palette.color(&Cell::colored('.', Color::Blue)));
+        assert_eq!(
+            [36, 114, 200],
+            palette.color(&Cell::colored('O', Color::Blue))
+        );
+        assert!("#=ff88".parse::<Palette>().is_err());
+        assert!("#ff8800".parse::<Palette>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_pick_formats() -> Result<()> {
+        assert_eq!(Format::Gif, Format::from_path(Path::new("out.GIF"))?);
+        assert!(Format::from_path(Path::new("out.jpg")).is_err());
+        assert!(Format::from_path(Path::new("out")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn should_scale_pixels() -> Result<()> {
+        let renderer = Renderer::new("out.png", Palette::new(), 2, 10.)?;
+        let (width, height, pixels) = renderer.pixels(&frame());
+        assert_eq!((6, 4), (width, height));
+        let red = terminal_rgb(Color::Red);
+        assert_eq!(&[red, red, red, red], &pixels[0..4]);
+        assert_eq!(Palette::new().background, pixels[23]);
+        Ok(())
+    }
+
+    #[test]
+    fn should_write_svg() -> Result<()> {
+        let renderer = Renderer::new("out.svg", Palette::new(), 10, 10.)?;
+        let svg = renderer.svg(&frame());
+        assert!(svg.contains("<title>a &lt; b</title>"));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#cd3131\"/>"));
+        // The space is left as background
+        assert_eq!(4, svg.matches("<rect").count());
+        Ok(())
+    }
+
+    #[test]
+    fn should_index_colours() {
+        let (buffer, palette) = Renderer::indexed(&[[1, 2, 3], [4, 5, 6], [1, 2, 3]]).unwrap();
+        assert_eq!(vec![0, 1, 0], buffer);
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], palette);
+        let rainbow = (0..=256)
+            .map(|i| [(i % 256) as u8, (i / 256) as u8, 0])
+            .collect::<Vec<_>>();
+        assert_eq!(None, Renderer::indexed(&rainbow));
+    }
+
+    #[test]
+    fn should_write_png_and_gif() -> Result<()> {
+        for name in ["frames.png", "frames.gif"] {
+            let path = temp_file(name);
+            let mut renderer = Renderer::new(&path, Palette::new(), 4, 10.)?;
+            assert!(renderer.show(&frame()));
+            assert!(renderer.show(&frame()));
+            renderer.finish()?;
+            let written = fs::read(&path)?;
+            let magic: &[u8] = match name.ends_with("png") {
+                true => b"\x89PNG",
+                false => b"GIF89a",
+            };
+            assert_eq!(magic, &written[..magic.len()]);
+        }
+        let mut empty = Renderer::new(temp_file("empty.png"), Palette::new(), 1, 10.)?;
+        assert!(empty.finish().is_err());
+        Ok(())
+    }
+}
@@ -1,12 +1,12 @@
-use crate::input::AdventInput;
-use anyhow::Result;
+use crate::{answer::Answer, input::AdventInput, progress::Progress, viz::Visualizer};
+use anyhow::{anyhow, Result};
 use gag::Gag;
-use std::{
-    fmt::Display,
-    marker::PhantomData,
-    time::{Duration, Instant},
-};
-pub trait Solver<T1: Display, T2: Display> {
+use std::time::{Duration, Instant};
+
+pub trait Solver {
+    type PartOne: Into<Answer>;
+    type PartTwo: Into<Answer>;
+
     const DAY: u8;
     const TITLE: &'static str;
 
@@ -14,88 +14,174 @@ pub trait Solver<T1: Display, T2: Display> {
         AdventInput::for_day(Self::DAY)
     }
 
-    fn solve(&self) -> Result<()> {
-        let part1 = self.part_one()?;
-        self.print_part_one(part1);
-        let part2 = self.part_two()?;
-        self.print_part_two(part2);
-        Ok(())
+    fn new() -> Self;
+
+    fn part_one(&self, input: &str) -> Result<Self::PartOne>;
+    fn part_two(&self, input: &str) -> Result<Self::PartTwo>;
+
+    /// The same as the plain parts, but with somewhere to report how they're
+    /// getting on. Only worth overriding for the slow ones.
+    fn part_one_with_progress(&self, input: &str, _progress: &Progress) -> Result<Self::PartOne> {
+        self.part_one(input)
+    }
+    fn part_two_with_progress(&self, input: &str, _progress: &Progress) -> Result<Self::PartTwo> {
+        self.part_two(input)
     }
 
-    fn new() -> Self;
+    /// Parses the input the way the parts do, without solving anything. Bad
+    /// input should come back as an error rather than a panic, which the fuzz
+    /// targets check.
+    fn parse_input(&self, _input: &str) -> Result<()> {
+        Ok(())
+    }
 
-    fn print_part_one<T: Display>(&self, result: T) {
-        println!("{}: {}", self.describe_part_one(), result);
+    /// Anything worth knowing about how a part went besides its answer (how
+    /// well a cache did, say), for `perf` to report. Most days have nothing.
+    fn part_stats(&self, _part: u8, _input: &str) -> Result<Option<String>> {
+        Ok(None)
     }
-    fn print_part_two<T: Display>(&self, result: T) {
-        println!("{}: {}", self.describe_part_two(), result);
+
+    /// Plays the puzzle out frame by frame, for the days worth watching.
+    fn visualize(&self, _input: &str, _viz: &Visualizer) -> Result<()> {
+        Err(anyhow!("Day {:02} has nothing to visualize", Self::DAY))
     }
+}
+
+/// The type-erased face of a `Solver`, so that days with different answer
+/// types can sit side by side in a `Vec<Box<dyn DynSolver>>`. Every `Solver`
+/// gets one for free.
+pub trait DynSolver {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn run_part(&self, part: u8, input: &str, progress: &Progress) -> Result<Answer>;
+    fn parse_input(&self, input: &str) -> Result<()>;
+
+    /// Runs the part on the real puzzle input.
+    fn run(&self, part: u8, progress: &Progress) -> Result<Answer>;
+    /// Visualizes the real puzzle input.
+    fn animate(&self, viz: &Visualizer) -> Result<()>;
+    /// The part's stats for the real puzzle input, if it keeps any.
+    fn stats(&self, part: u8) -> Result<Option<String>>;
 
-    fn part_one(&self) -> Result<T1>;
-    fn part_two(&self) -> Result<T2>;
+    fn solve(&self) -> Result<()> {
+        for part in [1, 2] {
+            let label = self.describe_part(part);
+            let progress = Progress::for_terminal(&label);
+            let answer = self.run(part, &progress)?;
+            progress.finish();
+            print_answer(&label, &answer);
+        }
+        Ok(())
+    }
 
     fn describe(&self) -> String {
-        format!("Day {:02}", Self::DAY)
+        format!("Day {:02}", self.day())
     }
-    fn describe_part(&self, part: isize) -> String {
+    fn describe_part(&self, part: u8) -> String {
         format!("{} Part {:02}", self.describe(), part)
     }
-    fn describe_part_one(&self) -> String {
-        self.describe_part(1)
-    }
-    fn describe_part_two(&self) -> String {
-        self.describe_part(2)
-    }
-}
-
-pub trait Measurable {
-    fn title(&self) -> String;
-    fn describe_part_one(&self) -> String;
-    fn describe_part_two(&self) -> String;
-    fn time_part_one(&self, iter: u8) -> Result<Duration>;
-    fn time_part_two(&self, iter: u8) -> Result<Duration>;
-}
 
-pub struct Measure<T: Solver<T1, T2>, T1: Display, T2: Display> {
-    solver: T,
-    _p1: PhantomData<T1>,
-    _p2: PhantomData<T2>,
+    /// The average time taken by `iter` runs of the part, or None if the
+    /// part hasn't been implemented, so there's nothing to time.
+    fn time_part(&self, part: u8, iter: u8) -> Result<Option<Duration>> {
+        time_execution(|| self.run(part, &Progress::hidden()), iter)
+    }
 }
-impl<T: Solver<T1, T2>, T1: Display, T2: Display> Measurable for Measure<T, T1, T2> {
-    fn title(&self) -> String {
-        String::from(T::TITLE)
+impl<S: Solver> DynSolver for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+    fn title(&self) -> &'static str {
+        S::TITLE
     }
-    fn describe_part_one(&self) -> String {
-        self.solver.describe_part_one()
+    fn run_part(&self, part: u8, input: &str, progress: &Progress) -> Result<Answer> {
+        match part {
+            1 => Ok(self.part_one_with_progress(input, progress)?.into()),
+            2 => Ok(self.part_two_with_progress(input, progress)?.into()),
+            _ => Err(anyhow!("Day {:02} has no part {part}", S::DAY)),
+        }
     }
-    fn describe_part_two(&self) -> String {
-        self.solver.describe_part_two()
+    fn parse_input(&self, input: &str) -> Result<()> {
+        Solver::parse_input(self, input)
     }
-    fn time_part_one(&self, iter: u8) -> Result<Duration> {
-        time_execution(|| self.solver.part_one(), iter)
+    fn run(&self, part: u8, progress: &Progress) -> Result<Answer> {
+        self.run_part(part, &self.input().get()?, progress)
     }
-    fn time_part_two(&self, iter: u8) -> Result<Duration> {
-        time_execution(|| self.solver.part_two(), iter)
+    fn animate(&self, viz: &Visualizer) -> Result<()> {
+        self.visualize(&self.input().get()?, viz)?;
+        viz.finish()
     }
+    fn stats(&self, part: u8) -> Result<Option<String>> {
+        self.part_stats(part, &self.input().get()?)
+    }
+}
+
+/// A boxed, freshly made solver for the given day.
+pub fn boxed<S: Solver + 'static>() -> Box<dyn DynSolver> {
+    Box::new(S::new())
 }
-impl<T: 'static + Solver<T1, T2>, T1: 'static + Display, T2: 'static + Display> Measure<T, T1, T2> {
-    pub fn get(solver: T) -> Box<dyn Measurable> {
-        Box::new(Measure {
-            solver,
-            _p1: PhantomData {},
-            _p2: PhantomData {},
-        })
+
+fn print_answer(label: &str, answer: &Answer) {
+    match answer {
+        // Start multi-line answers on their own line so they line up
+        Answer::Grid(_) => println!("{label}:\n{answer}"),
+        _ => println!("{label}: {answer}"),
     }
 }
 
-fn time_execution<F: Fn() -> Result<T>, T>(f: F, iter: u8) -> Result<Duration> {
+fn time_execution<F: Fn() -> Result<Answer>>(f: F, iter: u8) -> Result<Option<Duration>> {
     let gag = Gag::stdout()?;
     let mut tot = Duration::ZERO;
     for _ in 0..iter {
         let now = Instant::now();
-        f()?;
+        let answer = f()?;
         tot += now.elapsed();
+        if !answer.is_implemented() {
+            return Ok(None);
+        }
     }
     drop(gag);
-    Ok(tot / iter.into())
+    Ok(Some(tot / iter.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Example;
+    impl Solver for Example {
+        type PartOne = usize;
+        type PartTwo = String;
+
+        const DAY: u8 = 99;
+        const TITLE: &'static str = "Example";
+
+        fn new() -> Self {
+            Example {}
+        }
+
+        fn part_one(&self, input: &str) -> Result<usize> {
+            Ok(input.len())
+        }
+
+        fn part_two(&self, input: &str) -> Result<String> {
+            Ok(input.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn should_run_parts_dynamically() -> Result<()> {
+        let solver = boxed::<Example>();
+        assert_eq!(99, solver.day());
+        assert_eq!("Example", solver.title());
+        assert_eq!("Day 99 Part 02", solver.describe_part(2));
+        let progress = Progress::hidden();
+        assert_eq!(Answer::Int(9), solver.run_part(1, "forty-two", &progress)?);
+        assert_eq!(
+            Answer::from("FORTY-TWO"),
+            solver.run_part(2, "forty-two", &progress)?
+        );
+        assert!(solver.run_part(3, "forty-two", &progress).is_err());
+        Ok(())
+    }
 }
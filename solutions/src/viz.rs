@@ -0,0 +1,340 @@
+// Watching a puzzle work itself out. Solvers that have something worth seeing
+// build `Frame`s and hand them to a `Visualizer`; where those frames end up
+// (if anywhere) is decided by whoever made the visualizer.
+
+use crate::common::IPoint;
+use anyhow::Result;
+use colored::{Color, ColoredString, Colorize};
+use std::{
+    cell::{self, RefCell},
+    fmt::Display,
+    io::{stdin, stdout, Write},
+    rc::Rc,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// One character of a frame, and how to draw it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Option<Color>,
+    pub bold: bool,
+}
+impl Cell {
+    pub fn plain(ch: char) -> Self {
+        Cell {
+            ch,
+            color: None,
+            bold: false,
+        }
+    }
+
+    pub fn colored(ch: char, color: Color) -> Self {
+        Cell {
+            ch,
+            color: Some(color),
+            bold: false,
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Cell { bold: true, ..self }
+    }
+
+    fn styled(&self) -> ColoredString {
+        let mut s = self.ch.to_string().normal();
+        if let Some(color) = self.color {
+            s = s.color(color);
+        }
+        if self.bold {
+            s = s.bold();
+        }
+        s
+    }
+}
+
+/// A snapshot of a grid puzzle, with an optional caption underneath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    caption: String,
+}
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame::filled(width, height, Cell::plain(' '))
+    }
+
+    pub fn filled(width: usize, height: usize, cell: Cell) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![cell; width * height],
+            caption: String::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, p: IPoint) -> Option<usize> {
+        let in_bounds =
+            (0..self.width as isize).contains(&p.x) && (0..self.height as isize).contains(&p.y);
+        in_bounds.then(|| p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: IPoint) -> Option<&Cell> {
+        self.index(p).map(|i| &self.cells[i])
+    }
+
+    /// Anything outside the frame is quietly left out.
+    pub fn set(&mut self, p: IPoint, cell: Cell) {
+        if let Some(i) = self.index(p) {
+            self.cells[i] = cell;
+        }
+    }
+
+    pub fn caption(&self) -> &str {
+        &self.caption
+    }
+
+    pub fn with_caption(self, caption: impl Into<String>) -> Self {
+        Frame {
+            caption: caption.into(),
+            ..self
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The frame without any styling, one string per row.
+    pub fn plain_lines(&self) -> Vec<String> {
+        self.rows()
+            .map(|row| row.iter().map(|c| c.ch).collect())
+            .collect()
+    }
+}
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell.styled())?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{}", self.caption)
+    }
+}
+
+/// Somewhere for frames to go.
+pub trait FrameSink {
+    /// Returns false once it doesn't want any more frames.
+    fn show(&mut self, frame: &Frame) -> bool;
+
+    /// Called once the solver is done, for anything that needs wrapping up.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps every frame, e.g. so tests can look at them.
+impl FrameSink for Rc<RefCell<Vec<Frame>>> {
+    fn show(&mut self, frame: &Frame) -> bool {
+        self.borrow_mut().push(frame.clone());
+        true
+    }
+}
+
+/// The handle solvers draw through.
+pub struct Visualizer {
+    sink: Option<RefCell<Box<dyn FrameSink>>>,
+    stopped: cell::Cell<bool>,
+}
+impl Visualizer {
+    pub fn new(sink: impl FrameSink + 'static) -> Self {
+        Visualizer {
+            sink: Some(RefCell::new(Box::new(sink))),
+            stopped: cell::Cell::new(false),
+        }
+    }
+
+    /// A visualizer that nobody's watching.
+    pub fn hidden() -> Self {
+        Visualizer {
+            sink: None,
+            stopped: cell::Cell::new(false),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some() && !self.stopped.get()
+    }
+
+    /// Passes a frame on. The frame is only built if someone's still watching,
+    /// so it's fine to call this on every step.
+    pub fn show(&self, frame: impl FnOnce() -> Frame) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut sink = self.sink.as_ref().unwrap().borrow_mut();
+        if !sink.show(&frame()) {
+            self.stopped.set(true);
+        }
+    }
+
+    pub fn finish(&self) -> Result<()> {
+        match &self.sink {
+            Some(sink) => sink.borrow_mut().finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    TogglePause,
+    Step,
+    Quit,
+}
+impl Control {
+    fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "" | "n" => Some(Control::Step),
+            "p" => Some(Control::TogglePause),
+            "q" => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Animates frames in the terminal. The terminal stays line-buffered, so the
+/// controls are typed followed by Enter: `p` pauses and resumes, a bare Enter
+/// steps one frame while paused, and `q` stops watching (the solver carries on).
+pub struct Terminal {
+    delay: Duration,
+    paused: bool,
+    controls: Receiver<Control>,
+}
+impl Terminal {
+    pub fn new(fps: f32, paused: bool) -> Self {
+        let (tx, controls) = channel();
+        thread::spawn(move || {
+            for line in stdin().lines() {
+                let Ok(line) = line else { break };
+                if let Some(control) = Control::parse(&line) {
+                    if tx.send(control).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Terminal {
+            delay: Duration::from_secs_f32(1. / fps.max(0.1)),
+            paused,
+            controls,
+        }
+    }
+
+    /// Waits until it's time for the next frame. Returns false to quit.
+    fn wait(&mut self) -> bool {
+        loop {
+            let control = match self.paused {
+                true => self.controls.recv().ok(),
+                false => match self.controls.recv_timeout(self.delay) {
+                    Ok(control) => Some(control),
+                    Err(RecvTimeoutError::Timeout) => return true,
+                    // Nothing more coming from stdin, so just keep playing
+                    Err(RecvTimeoutError::Disconnected) => {
+                        thread::sleep(self.delay);
+                        return true;
+                    }
+                },
+            };
+            match control {
+                Some(Control::Quit) => return false,
+                Some(Control::TogglePause) => self.paused = !self.paused,
+                Some(Control::Step) if self.paused => return true,
+                Some(Control::Step) => (),
+                // Can't be unpaused any more, so don't leave it stuck
+                None => self.paused = false,
+            }
+        }
+    }
+}
+impl FrameSink for Terminal {
+    fn show(&mut self, frame: &Frame) -> bool {
+        let help = match self.paused {
+            true => "paused: Enter steps, p resumes, q stops",
+            false => "p pauses, q stops",
+        };
+        let mut out = stdout().lock();
+        // Clear the screen and go back to the top before each frame
+        let _ = write!(out, "\x1b[H\x1b[2J{frame}\n{}\n", help.dimmed());
+        let _ = out.flush();
+        drop(out);
+        self.wait()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_draw_frames() {
+        let mut frame = Frame::filled(3, 2, Cell::plain('.'));
+        frame.set(IPoint::new(1, 0), Cell::colored('#', Color::Red).bold());
+        // Off the edge, so ignored
+        frame.set(IPoint::new(3, 0), Cell::plain('#'));
+        frame.set(IPoint::new(0, -1), Cell::plain('#'));
+        assert_eq!(vec![".#.", "..."], frame.plain_lines());
+        assert_eq!(Some(&Cell::plain('.')), frame.get(IPoint::new(2, 1)));
+        assert_eq!(None, frame.get(IPoint::new(2, 2)));
+        let frame = frame.with_caption("step 1");
+        assert_eq!("step 1", frame.caption());
+    }
+
+    #[test]
+    fn should_only_build_frames_when_watched() {
+        let hidden = Visualizer::hidden();
+        hidden.show(|| panic!("Nobody's watching"));
+
+        let frames = Rc::new(RefCell::new(vec![]));
+        let viz = Visualizer::new(frames.clone());
+        viz.show(|| Frame::new(1, 1));
+        viz.show(|| Frame::new(2, 2));
+        assert_eq!(2, frames.borrow().len());
+    }
+
+    #[test]
+    fn should_stop_when_the_sink_has_had_enough() {
+        struct Once(usize);
+        impl FrameSink for Once {
+            fn show(&mut self, _: &Frame) -> bool {
+                self.0 += 1;
+                false
+            }
+        }
+        let viz = Visualizer::new(Once(0));
+        viz.show(|| Frame::new(1, 1));
+        viz.show(|| panic!("Should have stopped"));
+    }
+
+    #[test]
+    fn should_parse_controls() {
+        assert_eq!(Some(Control::Step), Control::parse("\n"));
+        assert_eq!(Some(Control::TogglePause), Control::parse("p"));
+        assert_eq!(Some(Control::Quit), Control::parse(" q "));
+        assert_eq!(None, Control::parse("what"));
+    }
+}
@@ -0,0 +1,83 @@
+// Runs every day against its real input and checks the answers still match
+// the ones recorded in answers.txt. That file is just the output of a plain
+// `cargo run`, so after solving a new day it can be regenerated with:
+//
+//     cargo run --release > solutions/tests/answers.txt
+//
+// Days whose input isn't there (e.g. a fresh clone without the input key) are
+// reported as ignored rather than failed.
+
+use anyhow::{anyhow, Result};
+use libtest_mimic::{Arguments, Failed, Trial};
+use solutions::{days, input::AdventInput, progress::Progress};
+use std::{collections::BTreeMap, env, path::Path};
+
+const ANSWERS: &str = include_str!("answers.txt");
+
+/// The recorded answer for each (day, part). Multi-line answers carry on
+/// below their label, as `cargo run` prints them.
+fn parse_answers(text: &str) -> Result<BTreeMap<(u8, u8), String>> {
+    let mut answers = BTreeMap::new();
+    let mut current: Option<((u8, u8), Vec<&str>)> = None;
+    for line in text.lines() {
+        let Some(rest) = line.strip_prefix("Day ") else {
+            match &mut current {
+                Some((_, lines)) => lines.push(line),
+                None => return Err(anyhow!("Answer without a day: {line}")),
+            }
+            continue;
+        };
+        let (label, answer) = rest
+            .split_once(':')
+            .ok_or(anyhow!("Invalid answer: {line}"))?;
+        let (day, part) = label
+            .split_once(" Part ")
+            .ok_or(anyhow!("Invalid answer: {line}"))?;
+        if let Some((key, lines)) = current.take() {
+            answers.insert(key, lines.join("\n"));
+        }
+        let answer = answer.trim();
+        let lines = match answer.is_empty() {
+            true => vec![],
+            false => vec![answer],
+        };
+        current = Some(((day.parse()?, part.parse()?), lines));
+    }
+    if let Some((key, lines)) = current {
+        answers.insert(key, lines.join("\n"));
+    }
+    Ok(answers)
+}
+
+fn check(day: u8, part: u8, expected: &str) -> Result<(), Failed> {
+    let solver = days::get(day).ok_or(format!("No solver for day {day}"))?;
+    let input = AdventInput::for_day(solver.day()).get()?;
+    let answer = solver.run_part(part, &input, &Progress::hidden())?;
+    match answer.to_string() == expected {
+        true => Ok(()),
+        false => Err(format!("expected {expected}, got {answer}").into()),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Arguments::from_args();
+    // Inputs (and the keyfile) are found relative to the workspace root
+    env::set_current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))?;
+
+    let answers = parse_answers(ANSWERS)?;
+    let mut trials = vec![];
+    for day in days::all().iter().map(|s| s.day()) {
+        let available = AdventInput::for_day(day).is_available();
+        for part in [1, 2] {
+            let name = format!("day{day:02}_part{part}");
+            let Some(expected) = answers.get(&(day, part)).cloned() else {
+                // Nothing recorded yet, so nothing to compare against
+                trials.push(Trial::test(name, || Ok(())).with_ignored_flag(true));
+                continue;
+            };
+            let trial = Trial::test(name, move || check(day, part, &expected));
+            trials.push(trial.with_ignored_flag(!available));
+        }
+    }
+    libtest_mimic::run(&args, trials).exit()
+}
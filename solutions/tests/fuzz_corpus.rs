@@ -0,0 +1,60 @@
+// Replays everything in the fuzz corpus (plus any crashes sitting in
+// fuzz/artifacts) through each day's parsers, so a parser that goes back to
+// panicking fails `cargo test` without needing nightly or cargo-fuzz. Days
+// without a fuzz target are reported as ignored.
+
+use libtest_mimic::{Arguments, Failed, Trial};
+use solutions::days;
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+/// Every saved input for the target, in a stable order.
+fn saved_inputs(target: &str) -> Vec<PathBuf> {
+    let fuzz = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz");
+    let mut files = ["corpus", "artifacts"]
+        .iter()
+        .filter_map(|dir| fs::read_dir(fuzz.join(dir).join(target)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn replay(day: u8, files: &[PathBuf]) -> Result<(), Failed> {
+    let solver = days::get(day).ok_or(format!("No solver for day {day}"))?;
+    let mut panicked = vec![];
+    for file in files {
+        let data = fs::read(file)?;
+        // The fuzz targets skip anything that isn't UTF-8 too
+        let Ok(input) = std::str::from_utf8(&data) else {
+            continue;
+        };
+        if panic::catch_unwind(AssertUnwindSafe(|| solver.parse_input(input))).is_err() {
+            panicked.push(file.display().to_string());
+        }
+    }
+    match panicked.is_empty() {
+        true => Ok(()),
+        false => Err(format!("Panicked on:\n{}", panicked.join("\n")).into()),
+    }
+}
+
+fn main() {
+    let args = Arguments::from_args();
+    let trials = days::all()
+        .iter()
+        .map(|solver| {
+            let day = solver.day();
+            let name = format!("parse_day{day:02}");
+            let files = saved_inputs(&name);
+            let ignored = files.is_empty();
+            Trial::test(name, move || replay(day, &files)).with_ignored_flag(ignored)
+        })
+        .collect();
+    libtest_mimic::run(&args, trials).exit()
+}